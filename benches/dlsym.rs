@@ -11,6 +11,9 @@ fn get_symbol(c: &mut Criterion) {
     c.bench_function("dlopen-rs:get", |b| {
         b.iter(|| unsafe { lib1.get::<fn(i32, i32) -> i32>("add").unwrap() })
     });
+    c.bench_function("dlopen-rs:get_cached", |b| {
+        b.iter(|| unsafe { lib1.get_cached::<fn(i32, i32) -> i32>("add").unwrap() })
+    });
     c.bench_function("libloading:get", |b| {
         b.iter(|| {
             unsafe { lib2.get::<fn(i32, i32) -> i32>("add".as_bytes()).unwrap() };
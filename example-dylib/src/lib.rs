@@ -52,3 +52,6 @@ fn args() {
 
 #[no_mangle]
 pub static HELLO: &str = "Hello!";
+
+#[no_mangle]
+pub static ANSWER: i32 = 42;
@@ -23,6 +23,52 @@ pub mod auxv {
     pub const AT_MINSIGSTKSZ: usize = 51;
 }
 
+pub mod dynamic {
+    pub const DT_PLTRELSZ: i64 = 2;
+    pub const DT_RELA: i64 = 7;
+    pub const DT_INIT: i64 = 12;
+    pub const DT_FINI: i64 = 13;
+    pub const DT_RELASZ: i64 = 8;
+    pub const DT_RELAENT: i64 = 9;
+    pub const DT_SONAME: i64 = 14;
+    pub const DT_RELSZ: i64 = 18;
+    pub const DT_RELENT: i64 = 19;
+    pub const DT_PLTREL: i64 = 20;
+    pub const DT_INIT_ARRAY: i64 = 25;
+    pub const DT_FINI_ARRAY: i64 = 26;
+    pub const DT_INIT_ARRAYSZ: i64 = 27;
+    pub const DT_FINI_ARRAYSZ: i64 = 28;
+    pub const DT_RELRSZ: i64 = 35;
+    pub const DT_RELR: i64 = 36;
+    pub const DT_FLAGS_1: i64 = 0x6fff_fffb;
+    pub const DT_VERSYM: i64 = 0x6fff_fff0;
+    pub const DT_VERDEFNUM: i64 = 0x6fff_fffd;
+    pub const DT_AUXILIARY: i64 = 0x7fff_fffd;
+    pub const DT_FILTER: i64 = 0x7fff_ffff;
+
+    pub const DF_1_GLOBAL: usize = 0x0000_0002;
+    pub const DF_1_NOOPEN: usize = 0x0000_0040;
+}
+
+pub mod segment {
+    pub const PF_X: u32 = 0x1;
+    pub const PF_W: u32 = 0x2;
+    pub const PF_R: u32 = 0x4;
+}
+
+pub mod symbol {
+    pub const STB_GLOBAL: u8 = 1;
+    pub const STT_TLS: u8 = 6;
+    pub const STT_GNU_IFUNC: u8 = 10;
+    /// The bits of `st_other` that hold the visibility.
+    pub const STV_MASK: u8 = 0x3;
+    pub const STV_PROTECTED: u8 = 3;
+}
+
+pub mod note {
+    pub const NT_GNU_BUILD_ID: u32 = 3;
+}
+
 pub mod elf {
     #[allow(unused_imports)]
     pub use elf_loader::elf::{
@@ -28,11 +28,8 @@ pub unsafe fn dl_find_object(pc: *const c_void, dlfo: *mut c_void) -> c_int {
     };
 
     let user_data = dso.inner.user_data();
-    let phdrs = dso.inner.phdrs().unwrap_or(&[]);
-
-    let eh_frame = phdrs
-        .iter()
-        .find(|p| p.program_type() == ElfProgramType::GNU_EH_FRAME)
+    let eh_frame = dso
+        .phdr(ElfProgramType::GNU_EH_FRAME)
         .map(|p| dso.base() + p.p_vaddr())
         .unwrap_or(0);
 
@@ -78,5 +75,7 @@ pub fn dl_find_dso_for_object(addr: *const c_void) -> *mut c_void {
 #[cfg(feature = "std")]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn _dl_find_object(pc: *const c_void, dlfo: *mut c_void) -> c_int {
-    unsafe { dl_find_object(pc, dlfo) }
+    super::catch_panic("_dl_find_object", -1, || unsafe {
+        dl_find_object(pc, dlfo)
+    })
 }
@@ -1,5 +1,5 @@
 use crate::{ElfLibrary, Error, Result, core_impl::MANAGER};
-use alloc::boxed::Box;
+use alloc::{boxed::Box, vec::Vec};
 use core::{
     ffi::{c_char, c_int, c_ulonglong, c_void},
     ptr::null_mut,
@@ -67,15 +67,41 @@ impl DlPhdrInfo<'_> {
 }
 
 impl ElfLibrary {
+    /// Get how many libraries were loaded and unloaded so far, as `(adds, subs)`.
+    ///
+    /// These are the `dlpi_adds` and `dlpi_subs` counters of
+    /// [`ElfLibrary::dl_iterate_phdr`]. A change in either means the set of loaded
+    /// libraries changed, e.g. so that cached unwind information must be refreshed.
+    pub fn link_map_stats() -> (u64, u64) {
+        let reader = crate::lock_read!(MANAGER);
+        (reader.adds(), reader.subs())
+    }
+
     /// Iterate over the program headers of all dynamic libraries.
+    ///
+    /// As with glibc, the main executable is always reported first; the remaining
+    /// libraries follow in load order.
+    ///
+    /// Iteration stops at the first error returned by `callback`, which is then returned.
+    ///
+    /// The libraries are collected before the first call, so `callback` may itself
+    /// `dlopen` or close libraries; those changes show up in the next iteration only.
     pub fn dl_iterate_phdr<F>(mut callback: F) -> Result<()>
     where
         F: FnMut(&DlPhdrInfo) -> Result<()>,
     {
-        let reader = crate::lock_read!(MANAGER);
-        let dlpi_adds = reader.adds();
-        let dlpi_subs = reader.subs();
-        for lib in reader.all_values() {
+        let (libs, dlpi_adds, dlpi_subs) = {
+            let reader = crate::lock_read!(MANAGER);
+            let main = reader.all_values().find(|lib| lib.name().is_empty());
+            let others = reader.all_values().filter(|lib| !lib.name().is_empty());
+            (
+                main.into_iter().chain(others).collect::<Vec<_>>(),
+                reader.adds(),
+                reader.subs(),
+            )
+        };
+        // The collected handles keep each library mapped until its callback has returned.
+        for lib in libs.iter() {
             let extra_data = lib.user_data();
             let phdrs = lib.phdrs().unwrap_or(&[]);
             if phdrs.is_empty() {
@@ -101,42 +127,50 @@ impl ElfLibrary {
     }
 }
 
+/// The callback of [`dl_iterate_phdr`]. It may unwind, so a Rust callback can panic.
 pub(crate) type CallBack =
-    unsafe extern "C" fn(info: *mut CDlPhdrInfo, size: usize, data: *mut c_void) -> c_int;
+    unsafe extern "C-unwind" fn(info: *mut CDlPhdrInfo, size: usize, data: *mut c_void) -> c_int;
 
 /// # Safety
 /// It is the same as `dl_iterate_phdr`.
+///
+/// A nonzero return from `callback` stops the iteration and is returned as-is.
+/// A panic raised while iterating, including one from `callback`, stops the iteration
+/// with -1 instead of unwinding into the C caller.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn dl_iterate_phdr(callback: Option<CallBack>, data: *mut c_void) -> c_int {
-    let Some(callback) = callback else {
-        return 0;
-    };
-    let f = |info: &DlPhdrInfo| {
-        let mut c_info = CDlPhdrInfo {
-            dlpi_addr: info.lib_base,
-            dlpi_name: info.lib_name,
-            dlpi_phdr: info.phdrs.as_ptr(),
-            dlpi_phnum: info.phdrs.len() as _,
-            dlpi_adds: info.dlpi_adds,
-            dlpi_subs: info.dlpi_subs,
-            dlpi_tls_modid: info.tls_modid,
-            dlpi_tls_data: info
-                .tls_data
-                .map(|data| data.as_ptr() as _)
-                .unwrap_or(null_mut()),
+    super::catch_panic("dl_iterate_phdr", -1, || {
+        let Some(callback) = callback else {
+            return 0;
         };
-        unsafe {
-            let ret = callback(&mut c_info, size_of::<CDlPhdrInfo>(), data);
-            if ret != 0 {
-                return Err(Error::IteratorPhdrError { err: Box::new(ret) });
-            }
+        let f = |info: &DlPhdrInfo| {
+            let mut c_info = CDlPhdrInfo {
+                dlpi_addr: info.lib_base,
+                dlpi_name: info.lib_name,
+                dlpi_phdr: info.phdrs.as_ptr(),
+                dlpi_phnum: info.phdrs.len() as _,
+                dlpi_adds: info.dlpi_adds,
+                dlpi_subs: info.dlpi_subs,
+                dlpi_tls_modid: info.tls_modid,
+                dlpi_tls_data: info
+                    .tls_data
+                    .map(|data| data.as_ptr() as _)
+                    .unwrap_or(null_mut()),
+            };
+            unsafe {
+                let ret = callback(&mut c_info, size_of::<CDlPhdrInfo>(), data);
+                if ret != 0 {
+                    return Err(Error::IteratorPhdrError { err: Box::new(ret) });
+                }
+            };
+            Ok(())
         };
-        Ok(())
-    };
-    if let Err(err) = ElfLibrary::dl_iterate_phdr(f) {
-        if let Error::IteratorPhdrError { err } = err {
-            return *err.downcast::<i32>().unwrap();
+        match ElfLibrary::dl_iterate_phdr(f) {
+            Ok(()) => 0,
+            // Only `i32` payloads originate from the callback above; anything else still
+            // has to stop the caller with a nonzero code.
+            Err(Error::IteratorPhdrError { err }) => err.downcast::<c_int>().map_or(-1, |ret| *ret),
+            Err(_) => -1,
         }
-    }
-    0
+    })
 }
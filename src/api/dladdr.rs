@@ -28,6 +28,18 @@ impl DlInfo {
         &self.dylib
     }
 
+    /// Name of the shared object containing addr
+    #[inline]
+    pub fn library_name(&self) -> &str {
+        self.dylib.name()
+    }
+
+    /// Base address at which the shared object is loaded
+    #[inline]
+    pub fn library_base(&self) -> usize {
+        self.dylib.base()
+    }
+
     /// Name of symbol whose definition overlaps addr
     #[inline]
     pub fn symbol_name(&self) -> Option<&str> {
@@ -64,56 +76,72 @@ impl ElfLibrary {
             "dladdr: Try to find the symbol information corresponding to [{:#x}]",
             addr
         );
-        addr2dso(addr).map(|dylib| {
-            let mut dl_info = DlInfo {
-                dylib,
-                sname: None,
-                saddr: 0,
-            };
-            let symtab = dl_info.dylib.inner.symtab();
-            let mut best_match: Option<(usize, &CStr)> = None;
-            for i in 0..symtab.count_syms() {
-                let (sym, syminfo) = symtab.symbol_idx(i);
-                if sym.st_value() == 0 || !sym.is_ok_bind() || !sym.is_ok_type() {
-                    continue;
-                }
-                let start = dl_info.dylib.base() + sym.st_value();
-                let end = start + sym.st_size();
-                if start <= addr && (sym.st_size() == 0 || addr < end) {
-                    if let Some((best_start, _)) = best_match {
-                        if start > best_start {
-                            if let Some(cname) = syminfo.cname() {
-                                best_match = Some((start, cname));
-                            }
-                        }
-                    } else {
-                        if let Some(cname) = syminfo.cname() {
-                            best_match = Some((start, cname));
-                        }
+        addr2dso(addr).map(|dylib| describe(dylib, addr))
+    }
+
+    /// Like [`ElfLibrary::dladdr`], but only looks at this library's own mapping.
+    ///
+    /// The registry is not consulted, so this answers for the handle at hand even when other
+    /// registered mappings overlap it. Returns `None` if `addr` lies outside the library.
+    pub fn dladdr_local(&self, addr: usize) -> Option<DlInfo> {
+        let start = self.base();
+        (start..start + self.mapped_len())
+            .contains(&addr)
+            .then(|| describe(self.clone(), addr))
+    }
+}
+
+/// Finds the symbol of `dylib` whose definition overlaps `addr`.
+fn describe(dylib: ElfLibrary, addr: usize) -> DlInfo {
+    let mut dl_info = DlInfo {
+        dylib,
+        sname: None,
+        saddr: 0,
+    };
+    let symtab = dl_info.dylib.inner.symtab();
+    let mut best_match: Option<(usize, &CStr)> = None;
+    for i in 0..symtab.count_syms() {
+        let (sym, syminfo) = symtab.symbol_idx(i);
+        if sym.st_value() == 0 || !sym.is_ok_bind() || !sym.is_ok_type() {
+            continue;
+        }
+        let start = dl_info.dylib.base() + sym.st_value();
+        let end = start + sym.st_size();
+        if start <= addr && (sym.st_size() == 0 || addr < end) {
+            if let Some((best_start, _)) = best_match {
+                if start > best_start {
+                    if let Some(cname) = syminfo.cname() {
+                        best_match = Some((start, cname));
                     }
                 }
+            } else {
+                if let Some(cname) = syminfo.cname() {
+                    best_match = Some((start, cname));
+                }
             }
-            if let Some((start, cname)) = best_match {
-                dl_info.sname = Some(unsafe { core::mem::transmute(cname) });
-                dl_info.saddr = start;
-            }
-            dl_info
-        })
+        }
+    }
+    if let Some((start, cname)) = best_match {
+        dl_info.sname = Some(unsafe { core::mem::transmute(cname) });
+        dl_info.saddr = start;
     }
+    dl_info
 }
 
 /// # Safety
 /// It is the same as `dladdr`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn dladdr(addr: *const c_void, info: *mut CDlinfo) -> c_int {
-    if let Some(dl_info) = ElfLibrary::dladdr(addr as usize) {
-        let info = unsafe { &mut *info };
-        info.dli_fbase = dl_info.dylib().base() as _;
-        info.dli_fname = dl_info.dylib().cname();
-        info.dli_saddr = dl_info.symbol_addr().unwrap_or(0) as _;
-        info.dli_sname = dl_info.sname.map_or(null(), |s| s.as_ptr());
-        1
-    } else {
-        0
-    }
+    super::catch_panic("dladdr", 0, || {
+        if let Some(dl_info) = ElfLibrary::dladdr(addr as usize) {
+            let info = unsafe { &mut *info };
+            info.dli_fbase = dl_info.library_base() as _;
+            info.dli_fname = dl_info.dylib().cname();
+            info.dli_saddr = dl_info.symbol_addr().unwrap_or(0) as _;
+            info.dli_sname = dl_info.sname.map_or(null(), |s| s.as_ptr());
+            1
+        } else {
+            0
+        }
+    })
 }
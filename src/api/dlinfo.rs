@@ -0,0 +1,62 @@
+use super::{is_live_handle, set_dlerror};
+use crate::{ElfLibrary, core_impl::ActiveTlsResolver};
+use core::ffi::{c_int, c_void};
+use elf_loader::tls::TlsResolver;
+
+/// Obtain the `struct link_map` of the handle.
+const RTLD_DI_LINKMAP: c_int = 2;
+/// Obtain the TLS module id of the handle, as used by `__tls_get_addr`.
+const RTLD_DI_TLS_MODID: c_int = 9;
+/// Obtain the calling thread's TLS block of the handle.
+const RTLD_DI_TLS_DATA: c_int = 10;
+
+/// # Safety
+/// It is the same as `dlinfo`.
+///
+/// Supports `RTLD_DI_LINKMAP`, `RTLD_DI_TLS_MODID` and `RTLD_DI_TLS_DATA`. The TLS requests
+/// fail with -1 for a library without thread-local storage. `RTLD_DI_TLS_DATA` allocates the
+/// calling thread's block if the thread has not used it yet, as `__tls_get_addr` does.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dlinfo(handle: *const c_void, request: c_int, info: *mut c_void) -> c_int {
+    super::catch_panic("dlinfo", -1, || {
+        if !is_live_handle(handle) {
+            set_dlerror("dlinfo: invalid handle");
+            return -1;
+        }
+        let lib = unsafe { &*(handle as *const ElfLibrary) };
+        match request {
+            RTLD_DI_LINKMAP => {
+                let link_map = lib
+                    .inner
+                    .user_data()
+                    .link_map
+                    .as_ref()
+                    .map_or(core::ptr::null(), |link_map| link_map.as_ref() as *const _);
+                unsafe { info.cast::<*const c_void>().write(link_map.cast()) };
+            }
+            RTLD_DI_TLS_MODID | RTLD_DI_TLS_DATA => {
+                let Some(modid) = lib.inner.tls_mod_id() else {
+                    set_dlerror(alloc::format!(
+                        "dlinfo: [{}] has no thread-local storage",
+                        lib.name()
+                    ));
+                    return -1;
+                };
+                if request == RTLD_DI_TLS_MODID {
+                    unsafe { info.cast::<usize>().write(modid.get()) };
+                } else {
+                    // Laid out like `tls_index`: the module id, then offset 0 for the block start.
+                    let index = [modid.get(), 0];
+                    let data =
+                        <ActiveTlsResolver as TlsResolver>::tls_get_addr(index.as_ptr().cast());
+                    unsafe { info.cast::<*mut c_void>().write(data.cast()) };
+                }
+            }
+            _ => {
+                set_dlerror(alloc::format!("dlinfo: unsupported request {}", request));
+                return -1;
+            }
+        }
+        0
+    })
+}
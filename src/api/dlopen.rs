@@ -1,20 +1,23 @@
-#[cfg(not(feature = "std"))]
-use crate::core_impl::ElfDylib;
-#[cfg(not(feature = "std"))]
-use crate::core_impl::shortname_from_name;
 use crate::{
     OpenFlags, Result,
     core_impl::{
-        AsFilename, DylibExt, ENVP, ElfLibrary, ExtraData, GlobalMeta, LibraryLookup, LoadedDylib,
-        MANAGER, Manager, new_loader, reserve_pending,
+        AsFilename, Builder, DylibExt, ENVP, ElfDylib, ElfLibrary, ExtraData, GlobalMeta,
+        LibraryLookup, LoadedDylib, MANAGER, Manager, filtered_get, filtered_scope_find,
+        new_dlopen_loader, protected_definitions, reserve_pending, shortname_from_name,
+        strong_undefined_symbols,
+    },
+    error::{find_lib_error, find_symbol_error},
+    utils::{
+        elf_check::{WRONG_ARCH, is_elf_input, is_host_object, validate_elf_header},
+        ld_cache::LdCache,
+        ld_debug::{self, ThreadTag, ld_debug},
+        linker_script::get_linker_script_libs,
     },
-    error::find_lib_error,
-    utils::{ld_cache::LdCache, linker_script::get_linker_script_libs},
 };
 use alloc::{
     borrow::ToOwned,
     boxed::Box,
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     format,
     string::{String, ToString},
     sync::Arc,
@@ -23,7 +26,10 @@ use alloc::{
 use core::{
     cell::RefCell,
     ffi::{CStr, c_char, c_int, c_void},
+    ops::ControlFlow,
+    sync::atomic::{AtomicBool, Ordering},
 };
+use elf_loader::elf::{ElfHeader, ElfPhdr, ElfProgramType};
 use elf_loader::image::{ModuleHandle, ModuleScope};
 use elf_loader::input::{ElfBinary, ElfFile, ElfReader, Path as LoaderPath, PathBuf as ElfPath};
 use elf_loader::linker::{
@@ -54,6 +60,10 @@ fn get_env(name: &str) -> Option<&'static str> {
 
 impl ElfLibrary {
     /// Get the main executable as an `ElfLibrary`. It is the same as `dlopen(NULL, RTLD_NOW)`.
+    ///
+    /// Symbol lookups through the returned handle search the global scope as it was when
+    /// the handle was created: the main executable, its dependencies and all `RTLD_GLOBAL`
+    /// libraries.
     pub fn this() -> ElfLibrary {
         let reader = crate::lock_read!(MANAGER);
         reader
@@ -70,18 +80,443 @@ impl ElfLibrary {
     /// let path = "/path/to/library.so";
     /// let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).expect("Failed to load library");
     /// ```
+    ///
+    /// With the `compression` feature, a path to a gzip or zstd compressed file is
+    /// decompressed into memory and loaded like [`ElfLibrary::dlopen_from_binary`], under
+    /// the path without its `.gz` or `.zst` extension. Objects that decompress to more than
+    /// 1 GiB are rejected.
     pub fn dlopen(path: impl AsFilename, flags: OpenFlags) -> Result<ElfLibrary> {
-        dlopen_impl(path.as_filename(), flags, None)
+        let path = path.as_filename();
+        #[cfg(feature = "compression")]
+        if path.contains('/')
+            && check_load_root(std::path::Path::new(path)).is_ok()
+            && let Some((bytes, name)) = crate::utils::compression::decompress_file(path)?
+        {
+            return Self::dlopen_from_binary(&bytes, name, flags);
+        }
+        dlopen_impl(path, flags, None, OpenHooks::default())
+    }
+
+    /// Load a shared library like [`ElfLibrary::dlopen`], reporting relocation progress.
+    ///
+    /// `f` is called once before each newly loaded library is relocated, with the registry
+    /// unlocked, so it may call into this crate. Returning [`ControlFlow::Break`] aborts the
+    /// load and rolls back every library added by it. Cancellation is only checked between
+    /// libraries: once a library's relocation has started it runs to completion.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// # use core::ops::ControlFlow;
+    /// let lib = ElfLibrary::dlopen_with_progress("/path/to/big.so", OpenFlags::RTLD_NOW, |p| {
+    ///     println!("relocating [{}] ({}/{})", p.name, p.current + 1, p.total);
+    ///     ControlFlow::Continue(())
+    /// });
+    /// ```
+    pub fn dlopen_with_progress(
+        path: impl AsFilename,
+        flags: OpenFlags,
+        mut f: impl FnMut(Progress<'_>) -> ControlFlow<()>,
+    ) -> Result<ElfLibrary> {
+        let hooks = OpenHooks {
+            progress: Some(&mut f),
+            ..OpenHooks::default()
+        };
+        dlopen_impl(path.as_filename(), flags, None, hooks)
+    }
+
+    /// Load a shared library like [`ElfLibrary::dlopen`], reading every object through `B`.
+    ///
+    /// [`Builder::create_object`] supplies the contents of the root and of every dependency
+    /// that is not loaded yet, e.g. to decrypt them. The usual search paths are still used to
+    /// find candidates.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use dlopen_rs::{Builder, ElfLibrary, OpenFlags, Result};
+    /// struct Plain;
+    ///
+    /// impl Builder for Plain {
+    ///     fn create_object(path: &str) -> Result<Vec<u8>> {
+    ///         Ok(std::fs::read(path)?)
+    ///     }
+    /// }
+    ///
+    /// let lib = ElfLibrary::dlopen_with_builder::<Plain>("/path/to/plugin.so", OpenFlags::RTLD_NOW);
+    /// ```
+    pub fn dlopen_with_builder<B: Builder>(
+        path: impl AsFilename,
+        flags: OpenFlags,
+    ) -> Result<ElfLibrary> {
+        let hooks = OpenHooks {
+            builder: Some(B::create_object),
+            ..OpenHooks::default()
+        };
+        dlopen_impl(path.as_filename(), flags, None, hooks)
+    }
+
+    /// Load a shared library like [`ElfLibrary::dlopen`], resolving symbols through `resolver`
+    /// first.
+    ///
+    /// `resolver` is consulted before the usual scopes for every relocation of the libraries
+    /// newly loaded by this call, including lazy bindings resolved later. Returning `None`
+    /// falls back to the normal lookup. Libraries that are already loaded keep their existing
+    /// bindings.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// extern "C" fn fixed_time(_: *mut i64) -> i64 {
+    ///     0
+    /// }
+    ///
+    /// let lib = ElfLibrary::dlopen_with_resolver("/path/to/plugin.so", OpenFlags::RTLD_NOW, |name| {
+    ///     (name == "time").then_some(fixed_time as *const ())
+    /// });
+    /// ```
+    pub fn dlopen_with_resolver(
+        path: impl AsFilename,
+        flags: OpenFlags,
+        resolver: impl Fn(&str) -> Option<*const ()> + Send + Sync + 'static,
+    ) -> Result<ElfLibrary> {
+        let hooks = OpenHooks {
+            resolver: Some(Arc::new(resolver)),
+            ..OpenHooks::default()
+        };
+        dlopen_impl(path.as_filename(), flags, None, hooks)
+    }
+
+    /// Load a shared library like [`ElfLibrary::dlopen`], without running constructors.
+    ///
+    /// The library and the dependencies newly loaded with it are mapped and relocated, but
+    /// their `DT_INIT` and `DT_INIT_ARRAY` functions only run once
+    /// [`ElfLibrary::run_constructors`] is called. Until then, code of the library may see
+    /// state its constructors have not set up yet. Libraries that were already loaded keep
+    /// their constructors as they ran. A later `dlopen` without deferral that returns the
+    /// library, or one depending on it, runs the pending constructors before returning.
+    ///
+    /// Destructors of a library are skipped at unload as long as its constructors have not
+    /// run.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen_no_init("/path/to/plugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// let version = unsafe { lib.get::<*const u32>("PLUGIN_VERSION").unwrap() };
+    /// lib.run_constructors();
+    /// ```
+    pub fn dlopen_no_init(path: impl AsFilename, flags: OpenFlags) -> Result<ElfLibrary> {
+        let hooks = OpenHooks {
+            defer_init: true,
+            ..OpenHooks::default()
+        };
+        dlopen_impl(path.as_filename(), flags, None, hooks)
+    }
+
+    /// Set what happens when a dependency of a library being loaded can not be found.
+    ///
+    /// The policy applies to every later `dlopen` in the process. See [`MissingDepPolicy`].
+    pub fn set_missing_dep_policy(policy: MissingDepPolicy) {
+        LENIENT_MISSING_DEPS.store(policy == MissingDepPolicy::Lenient, Ordering::Relaxed);
+    }
+
+    /// Let dependencies be found next to the library that needs them.
+    ///
+    /// When enabled, a `DT_NEEDED` entry without a slash that is not found through
+    /// `DT_RPATH`, `LD_LIBRARY_PATH` or `DT_RUNPATH` is looked for in the directory of the
+    /// library that needs it, before the ld.so cache and the default paths. glibc has no
+    /// such step, so it is off by default. The setting applies to every later `dlopen` in the
+    /// process.
+    pub fn set_sibling_lookup(enabled: bool) {
+        SIBLING_LOOKUP.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Confine every library read from disk to the directory `root`, or lift the confinement
+    /// with `None`.
+    ///
+    /// While a root is set, each path is resolved through `..` and symbolic links before the
+    /// file is opened, and one that ends up outside `root` is refused with
+    /// [`Error::InvalidPath`](crate::Error::InvalidPath). This covers dependencies too, so
+    /// those not already loaded must live under `root` as well. Libraries loaded from memory
+    /// or through a [`Builder`] are not affected. The setting applies to every later `dlopen`
+    /// in the process.
+    ///
+    /// The check and the open are separate steps, so a tree that others may write to can
+    /// still be raced; the root itself should only be writable by trusted parties.
+    #[cfg(feature = "std")]
+    pub fn set_load_root(root: Option<&std::path::Path>) -> Result<()> {
+        let root = root.map(std::fs::canonicalize).transpose()?;
+        *LOAD_ROOT.write() = root;
+        Ok(())
     }
 
-    /// Load a shared library from bytes. It is the same as dlopen. However, it can also be used in the no_std environment,
-    /// and it will look for dependent libraries in those manually opened dynamic libraries.
+    /// Load the first of several candidate paths that can be loaded.
+    ///
+    /// The paths are tried in order, e.g. a development build before the installed copy.
+    /// If none loads, the returned [`Error::FindLibError`](crate::Error::FindLibError) lists
+    /// every attempted path with the reason it failed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen_any(
+    ///     &["target/debug/libplugin.so", "/usr/lib/app/libplugin.so"],
+    ///     OpenFlags::RTLD_NOW,
+    /// );
+    /// ```
+    pub fn dlopen_any(paths: &[&str], flags: OpenFlags) -> Result<ElfLibrary> {
+        let mut msg = String::from("can not load any of the candidate paths:");
+        for path in paths {
+            match Self::dlopen(*path, flags) {
+                Ok(lib) => return Ok(lib),
+                Err(err) => msg.push_str(&format!("\n  [{}]: {}", path, err)),
+            }
+        }
+        if paths.is_empty() {
+            msg.push_str(" none given");
+        }
+        Err(find_lib_error(msg))
+    }
+
+    /// Load a shared library from bytes. It is the same as dlopen. However, it can also be used in the no_std environment.
+    ///
+    /// Only the library itself comes from `bytes`. Its dependencies are first looked up among
+    /// the loaded libraries and otherwise searched on disk like those of
+    /// [`ElfLibrary::dlopen`], with `$ORIGIN` taken from `path`. Without `std` there is no
+    /// file system to search, so they must already be loaded.
+    ///
+    /// The bytes must be a 64-bit little-endian ELF image whose program header table lies
+    /// within them; anything else is rejected with
+    /// [`Error::FindLibError`](crate::Error::FindLibError) before loading starts.
     pub fn dlopen_from_binary(
         bytes: &[u8],
         path: impl AsFilename,
         flags: OpenFlags,
     ) -> Result<ElfLibrary> {
-        dlopen_impl(path.as_filename(), flags, Some(bytes))
+        validate_elf_header(bytes)?;
+        dlopen_impl(path.as_filename(), flags, Some(bytes), OpenHooks::default())
+    }
+
+    /// Load a shared library from a [`Path`](std::path::Path).
+    ///
+    /// This behaves exactly like [`ElfLibrary::dlopen`]. Libraries are registered and
+    /// deduplicated by their name, so paths that are not valid UTF-8 are rejected with
+    /// [`Error::InvalidPath`](crate::Error::InvalidPath) rather than given a lossy name that
+    /// another path could share.
+    #[cfg(feature = "std")]
+    pub fn dlopen_path(path: &std::path::Path, flags: OpenFlags) -> Result<ElfLibrary> {
+        let path = path.to_str().ok_or(crate::Error::InvalidPath)?;
+        dlopen_impl(path, flags, None, OpenHooks::default())
+    }
+
+    /// Load a shared library from a directory given as an open file descriptor.
+    ///
+    /// `relative_name` must be a plain file name in that directory: names that are empty,
+    /// `.` or `..`, or contain a `/` are rejected with
+    /// [`Error::InvalidPath`](crate::Error::InvalidPath), and the file itself may not be a
+    /// symbolic link, so the library cannot come from outside the directory. It is opened
+    /// with `openat` and loaded like [`ElfLibrary::dlopen_from_binary`] under its full path,
+    /// found through `/proc/self/fd`.
+    ///
+    /// Libraries are registered by file name, so loading a second file of the same name,
+    /// e.g. the same plugin name from another directory, fails while the first one is
+    /// loaded instead of returning it.
+    ///
+    /// Dependencies are still found through the usual search paths, so this suits
+    /// self-contained plugins, or dependencies that are already loaded.
+    #[cfg(feature = "std")]
+    pub fn dlopen_at(
+        dir_fd: std::os::fd::RawFd,
+        relative_name: &str,
+        flags: OpenFlags,
+    ) -> Result<ElfLibrary> {
+        use std::{
+            io::Read,
+            os::{fd::FromRawFd, unix::fs::MetadataExt},
+        };
+        if matches!(relative_name, "" | "." | "..") || relative_name.contains('/') {
+            return Err(crate::Error::InvalidPath);
+        }
+        let name = std::ffi::CString::new(relative_name).map_err(|_| crate::Error::InvalidPath)?;
+        let fd = unsafe {
+            libc::openat(
+                dir_fd,
+                name.as_ptr(),
+                libc::O_RDONLY | libc::O_CLOEXEC | libc::O_NOFOLLOW,
+            )
+        };
+        if fd < 0 {
+            return Err(find_lib_error(format!(
+                "can not open [{}] in directory fd {}: {}",
+                relative_name,
+                dir_fd,
+                std::io::Error::last_os_error()
+            )));
+        }
+        let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+        let dir = std::fs::read_link(format!("/proc/self/fd/{dir_fd}"))?;
+        let dir = dir.to_str().ok_or(crate::Error::InvalidPath)?;
+        let path = format!("{}/{}", dir.trim_end_matches('/'), relative_name);
+
+        let metadata = file.metadata()?;
+        let loaded = crate::lock_read!(MANAGER).visible_loaded(relative_name);
+        if let Some(loaded) = loaded
+            && loaded
+                .user_data()
+                .file_identity
+                .is_none_or(|id| (id.dev, id.ino) != (metadata.dev(), metadata.ino()))
+        {
+            return Err(find_lib_error(format!(
+                "can not load [{}]: a different file of that name is loaded from [{}]",
+                path,
+                loaded.name()
+            )));
+        }
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Self::dlopen_from_binary(&bytes, path.as_str(), flags)
+    }
+
+    /// Links a shared library that the caller has already mapped, such as an image restored
+    /// from a checkpoint or placed in a shared memory region.
+    ///
+    /// `base` is the load bias of the image, which must be linked at address 0 so that its
+    /// ELF header sits at `base`, and every `PT_LOAD` segment must lie within
+    /// `[base, base + len)`. The library is relocated against the current scope and
+    /// registered like one opened by [`ElfLibrary::dlopen`], but its memory is never
+    /// unmapped by this crate.
+    ///
+    /// # Safety
+    ///
+    /// The mapping must hold a faithful, not yet relocated image with writable segments where
+    /// the loader applies relocations, and must stay mapped until the library is unloaded.
+    pub unsafe fn from_mapped(
+        base: usize,
+        len: usize,
+        name: impl AsFilename,
+        flags: OpenFlags,
+    ) -> Result<ElfLibrary> {
+        let name = name.as_filename();
+        if base == 0 || len < size_of::<ElfHeader>() {
+            return Err(find_lib_error(format!(
+                "mapped image of [{}] is too small",
+                name
+            )));
+        }
+        validate_elf_header(unsafe { core::slice::from_raw_parts(base as *const u8, len) })
+            .map_err(|err| find_lib_error(format!("mapped image of [{}]: {}", name, err)))?;
+        let ehdr = unsafe { &*(base as *const ElfHeader) };
+        let phdrs = unsafe {
+            core::slice::from_raw_parts((base + ehdr.e_phoff()) as *const ElfPhdr, ehdr.e_phnum())
+        }
+        .to_vec();
+        if phdrs
+            .iter()
+            .filter(|phdr| phdr.program_type() == ElfProgramType::LOAD)
+            .any(|phdr| phdr.p_vaddr().saturating_add(phdr.p_memsz()) > len)
+        {
+            return Err(find_lib_error(format!(
+                "segments of [{}] lie outside the mapping",
+                name
+            )));
+        }
+
+        let entry = ehdr.e_entry().wrapping_add(base);
+        let raw =
+            unsafe { new_dlopen_loader(false).load_mapped_dynamic(name, base, phdrs, entry) }?;
+        dlopen_mapped_root(name, raw, flags)
+    }
+}
+
+/// What `dlopen` does about a `DT_NEEDED` entry that can not be found, see
+/// [`ElfLibrary::set_missing_dep_policy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingDepPolicy {
+    /// Fail the whole `dlopen`, like glibc.
+    #[default]
+    Strict,
+    /// Log a warning and load the library anyway. Its symbols must then be provided by the
+    /// global scope, or the relocations referencing them fail as usual.
+    Lenient,
+}
+
+static LENIENT_MISSING_DEPS: AtomicBool = AtomicBool::new(false);
+static SIBLING_LOOKUP: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "std")]
+static LOAD_ROOT: spin::RwLock<Option<std::path::PathBuf>> = spin::RwLock::new(None);
+
+/// Refuses a path that resolves to a file outside the root set by
+/// [`ElfLibrary::set_load_root`]. A missing file is reported as such, so searches go on.
+#[cfg(feature = "std")]
+fn check_load_root(path: &std::path::Path) -> Result<()> {
+    let root = LOAD_ROOT.read();
+    let Some(root) = root.as_ref() else {
+        return Ok(());
+    };
+    let resolved = std::fs::canonicalize(path)?;
+    if !resolved.starts_with(root) {
+        log::warn!(
+            "dlopen: [{}] resolves to [{}], outside the load root [{}]",
+            path.display(),
+            resolved.display(),
+            root.display()
+        );
+        return Err(crate::Error::InvalidPath);
+    }
+    Ok(())
+}
+
+/// Relocation progress reported by [`ElfLibrary::dlopen_with_progress`].
+///
+/// Progress is counted in libraries, not in relocations: a load of a single large library
+/// is reported once, before its relocation starts.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress<'a> {
+    /// The name of the library about to be relocated.
+    pub name: &'a str,
+    /// The number of libraries of this load already handed to relocation.
+    pub current: usize,
+    /// The number of libraries this load relocates, leaving out the already loaded
+    /// libraries they depend on.
+    pub total: usize,
+}
+
+/// Optional caller hooks threaded through a `dlopen` operation.
+#[derive(Default)]
+struct OpenHooks<'h> {
+    progress: Option<&'h mut dyn FnMut(Progress<'_>) -> ControlFlow<()>>,
+    builder: Option<ObjectBuilder>,
+    resolver: Option<SymbolResolver>,
+    defer_init: bool,
+}
+
+/// Resolves symbols ahead of the normal scopes, see [`ElfLibrary::dlopen_with_resolver`].
+type SymbolResolver = Arc<dyn Fn(&str) -> Option<*const ()> + Send + Sync>;
+
+/// Reads the contents of an object, see [`Builder::create_object`].
+type ObjectBuilder = fn(&str) -> Result<Vec<u8>>;
+
+/// Keeps the objects read by an [`ObjectBuilder`] alive until the load is finished.
+#[derive(Default)]
+struct ObjectArena {
+    objects: RefCell<Vec<*mut [u8]>>,
+}
+
+impl ObjectArena {
+    fn keep(&self, bytes: Vec<u8>) -> &[u8] {
+        let object = Box::into_raw(bytes.into_boxed_slice());
+        self.objects.borrow_mut().push(object);
+        // SAFETY: the allocation is only freed when the arena is dropped, which the
+        // returned borrow of `self` prevents.
+        unsafe { &*object }
+    }
+}
+
+impl Drop for ObjectArena {
+    fn drop(&mut self) {
+        for object in self.objects.get_mut().drain(..) {
+            drop(unsafe { Box::from_raw(object) });
+        }
     }
 }
 
@@ -95,6 +530,11 @@ struct OpenShared<'a> {
     lock: RefCell<Option<RwLockWriteGuard<'a, Manager>>>,
     /// Loading flags for this operation.
     flags: OpenFlags,
+    /// The relocation scope of an eager load, kept to explain a failed relocation.
+    relocation_scope: RefCell<Option<Arc<[LoadedDylib]>>>,
+    /// The error that made the resolver or the planner fail the load, returned in place of
+    /// the linker's copy of it.
+    failure: RefCell<Option<crate::Error>>,
 }
 
 struct OpenContext<'a> {
@@ -110,15 +550,16 @@ enum LinkRoot<'bytes> {
         key: String,
         bytes: Option<&'bytes [u8]>,
     },
-    #[cfg(not(feature = "std"))]
-    Mapped { key: String, raw: ElfDylib },
+    Mapped {
+        key: String,
+        raw: ElfDylib,
+    },
 }
 
 impl<'bytes> LinkRoot<'bytes> {
     fn bytes(&self) -> Option<&'bytes [u8]> {
         match self {
             Self::Load { bytes, .. } => *bytes,
-            #[cfg(not(feature = "std"))]
             Self::Mapped { .. } => None,
         }
     }
@@ -142,14 +583,24 @@ impl<'a> Drop for OpenContext<'a> {
 
 impl<'a> OpenContext<'a> {
     fn new(mut flags: OpenFlags) -> Self {
+        ld_debug::init(|| get_env("DLOPEN_RS_DEBUG"));
         if get_env("LD_BIND_NOW").is_some() {
             flags |= OpenFlags::RTLD_NOW;
+        } else if get_env("LD_BIND_NOT").is_some() {
+            // The lazy fixup stub belongs to elf_loader and always writes the resolved
+            // address back into the GOT, so re-resolving on every call cannot be honored.
+            static WARN_BIND_NOT: spin::Once = spin::Once::new();
+            WARN_BIND_NOT.call_once(|| {
+                log::warn!("dlopen: LD_BIND_NOT is not supported, lazy bindings are cached")
+            });
         }
         let lock = crate::lock_write!(MANAGER);
         Self {
             shared: OpenShared {
                 lock: RefCell::new(Some(lock)),
                 flags,
+                relocation_scope: RefCell::new(None),
+                failure: RefCell::new(None),
             },
             added_names: BTreeSet::new(),
             committed: false,
@@ -170,6 +621,19 @@ impl<'a> OpenShared<'a> {
         f(manager)
     }
 
+    /// Hands `err` to the linker, keeping it to be returned as is once the load fails.
+    ///
+    /// The linker only carries its own error type, which would turn every error of this
+    /// crate into a [`crate::Error::LoaderError`].
+    fn fail(&self, err: crate::Error) -> elf_loader::Error {
+        if let crate::Error::LoaderError { err } = err {
+            return err;
+        }
+        let linker_err = elf_loader::CustomError::Message(err.to_string().into()).into();
+        self.failure.borrow_mut().get_or_insert(err);
+        linker_err
+    }
+
     fn take_lock(&self) -> Option<RwLockWriteGuard<'a, Manager>> {
         self.lock.borrow_mut().take()
     }
@@ -228,7 +692,9 @@ impl<'a> OpenShared<'a> {
         if let Some(lib) = entry.as_ref().filter(|lib| {
             lib.is_relocated() || added_names.is_some_and(|names| names.contains(lib.shortname()))
         }) {
-            log::info!(
+            ld_debug!(
+                LIBS,
+                info,
                 "dlopen: Found existing library by inode match: requested [{}], existing [{}] (dev={}, ino={})",
                 shortname,
                 lib.name().unwrap_or(lib.shortname()),
@@ -243,15 +709,36 @@ impl<'a> OpenShared<'a> {
         Ok(entry)
     }
 
-    fn prepare_relocation(&self, group_scope: &ModuleScope) -> ModuleScope {
+    /// Builds the relocation scope, also returning it when one of its libraries is a filter.
+    fn prepare_relocation(
+        &self,
+        group_scope: &ModuleScope,
+    ) -> (ModuleScope, Option<Arc<[LoadedDylib]>>) {
         let group_scope = group_scope
             .iter()
             .filter_map(|module| module.as_loaded::<ExtraData>().cloned())
             .collect::<Vec<_>>();
         let relocation_scope =
             self.with_manager_mut(|manager| manager.relocation_scope(&group_scope, self.flags));
+        #[cfg(feature = "trace-resolution")]
+        log::debug!(
+            "resolution: relocation scope deepbind: {} order: {:?}",
+            self.flags.is_deepbind(),
+            relocation_scope
+                .iter()
+                .map(|lib| lib.shortname())
+                .collect::<Vec<_>>()
+        );
         drop(self.take_lock());
-        ModuleScope::new(relocation_scope.iter())
+        if self.flags.is_now() {
+            *self.relocation_scope.borrow_mut() = Some(relocation_scope.clone());
+        }
+        let scope = ModuleScope::new(relocation_scope.iter());
+        let filtered = relocation_scope
+            .iter()
+            .any(|lib| lib.user_data().is_filter())
+            .then_some(relocation_scope);
+        (scope, filtered)
     }
 }
 
@@ -262,7 +749,6 @@ impl<'a> OpenContext<'a> {
         }
     }
 
-    #[cfg(not(feature = "std"))]
     fn reserve_pending(&mut self, shortname: &str, full_name: &str) {
         let shortname = self.shared.with_manager_mut(|manager| {
             reserve_pending(
@@ -276,9 +762,51 @@ impl<'a> OpenContext<'a> {
         self.added_names.insert(shortname);
     }
 
+    /// Replaces the error of a failed eager load with the list of every symbol the new
+    /// libraries reference but nothing in their relocation scope defines, if there is any.
+    ///
+    /// Relocation stops at the first symbol it can not bind, so this goes over the symbol
+    /// tables of the mapped libraries again to report all of them at once.
+    fn describe_unresolved(
+        &self,
+        resolver: Option<&SymbolResolver>,
+        err: crate::Error,
+    ) -> crate::Error {
+        let Some(scope) = self.shared.relocation_scope.take() else {
+            return err;
+        };
+        let is_defined = |name: &str| {
+            resolver.is_some_and(|resolver| resolver(name).is_some())
+                || scope
+                    .iter()
+                    .any(|lib| unsafe { filtered_get::<()>(lib, name) }.is_some())
+        };
+        let mut missing: Vec<String> = Vec::new();
+        for lib in scope
+            .iter()
+            .filter(|lib| self.added_names.contains(lib.shortname()))
+        {
+            for name in strong_undefined_symbols(lib) {
+                if !missing.contains(&name) && !is_defined(&name) {
+                    missing.push(name);
+                }
+            }
+        }
+        if missing.is_empty() {
+            return err;
+        }
+        find_symbol_error(format!(
+            "unresolved symbols: {} ({})",
+            missing.join(", "),
+            err
+        ))
+    }
+
     fn finish_existing(&mut self, path: &str, lib: LibraryLookup<'static>) -> ElfLibrary {
         let canonical_shortname = lib.into_shortname_owned();
-        log::info!(
+        ld_debug!(
+            LIBS,
+            info,
             "dlopen: Found existing library [{}] (canonical name: {})",
             path,
             canonical_shortname
@@ -292,8 +820,53 @@ impl<'a> OpenContext<'a> {
         elf_lib
     }
 
-    fn try_existing(&mut self, path: &str) -> Result<Option<ElfLibrary>> {
+    /// Retires the registry entry for `path` if the file was replaced since it was loaded,
+    /// so the new contents get loaded instead of the cached library.
+    ///
+    /// Only concrete paths are checked, which keeps soname lookups free of an extra `stat`.
+    fn evict_replaced(&mut self, path: &str, shortname: &str) {
+        let Ok(current) = crate::os::get_file_inode(path) else {
+            return;
+        };
+        self.shared.with_manager_mut(|manager| {
+            if !manager
+                .lookup(shortname)
+                .is_some_and(|lib| lib.is_relocated())
+            {
+                return;
+            }
+            let Some(lib) = manager.visible_loaded(shortname) else {
+                return;
+            };
+            if lib.name() != path
+                || manager
+                    .flags(shortname)
+                    .is_none_or(|flags| flags.is_nodelete())
+            {
+                return;
+            }
+            if lib
+                .user_data()
+                .file_identity
+                .is_some_and(|stored| !stored.is_unchanged(&current))
+            {
+                log::info!(
+                    "dlopen: [{}] changed on disk since it was loaded, loading it again",
+                    path
+                );
+                // Handles to the old copy still unload it through its retired key.
+                manager.retire(shortname);
+            }
+        });
+    }
+
+    /// `in_memory` marks loads whose image is supplied by the caller or a [`Builder`], so
+    /// `path` does not have to exist on disk.
+    fn try_existing(&mut self, path: &str, in_memory: bool) -> Result<Option<ElfLibrary>> {
         let shortname = path.rsplit_once('/').map_or(path, |(_, name)| name);
+        if path.contains('/') {
+            self.evict_replaced(path, shortname);
+        }
         // Step 1: fast name/alias lookup — no stat.
         // Step 2: on miss, stat once and fall back to inode lookup.
         if let Some(lib) = self.shared.wait_for_library(None, shortname) {
@@ -304,7 +877,7 @@ impl<'a> OpenContext<'a> {
             Ok(Some(lib)) => Ok(Some(self.finish_existing(path, lib))),
             Ok(None) => Ok(None),
             Err(e) => {
-                if path.contains('/') {
+                if path.contains('/') && !in_memory {
                     // full path lookups should report errors
                     Err(e)
                 } else {
@@ -320,6 +893,11 @@ impl<'a> OpenContext<'a> {
         link_ctx: &LinkContext<String, ExtraData, GlobalMeta>,
         committed: impl IntoIterator<Item = KeyId>,
     ) {
+        let committed = committed.into_iter().collect::<Vec<_>>();
+        let lazy = self.shared.flags.is_lazy() && !self.shared.flags.is_now();
+        for lib in committed.iter().filter_map(|id| link_ctx.get(*id)) {
+            lib.user_data().lazy_binding.store(lazy, Ordering::Relaxed);
+        }
         let mut lock = self
             .shared
             .take_lock()
@@ -340,17 +918,7 @@ impl<'a> OpenContext<'a> {
     fn finish(mut self, deps: Arc<[LoadedDylib]>) -> ElfLibrary {
         self.committed = true;
         let core = deps[0].clone();
-        ElfLibrary {
-            inner: core,
-            deps: Some(deps),
-        }
-    }
-}
-
-fn into_linker_error(err: crate::error::Error) -> elf_loader::Error {
-    match err {
-        crate::error::Error::LoaderError { err } => err,
-        other => elf_loader::CustomError::Message(other.to_string().into()).into(),
+        ElfLibrary::new_handle(core, deps)
     }
 }
 
@@ -359,6 +927,14 @@ struct LinkResolver<'ctx, 'mgr, 'bytes> {
     added_names: &'ctx mut BTreeSet<String>,
     root_request: String,
     root_bytes: Option<&'bytes [u8]>,
+    builder: Option<(ObjectBuilder, &'bytes ObjectArena)>,
+    /// Whether the library being resolved is the root rather than a dependency.
+    resolving_root: bool,
+    /// The `DT_RPATH` directories of the loader chain of each library added by this load,
+    /// which its own dependencies search after its `DT_RPATH`, unless it has `DT_RUNPATH`.
+    inherited_rpath: BTreeMap<String, Vec<ElfPath>>,
+    /// The loader chain recorded for libraries reserved while resolving a dependency.
+    loader_rpath: Vec<ElfPath>,
 }
 
 struct DlopenVisible<'ctx, 'mgr> {
@@ -399,12 +975,17 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
         added_names: &'ctx mut BTreeSet<String>,
         root_request: &str,
         root_bytes: Option<&'bytes [u8]>,
+        builder: Option<(ObjectBuilder, &'bytes ObjectArena)>,
     ) -> Self {
         Self {
             shared,
             added_names,
             root_request: root_request.to_owned(),
             root_bytes,
+            builder,
+            resolving_root: false,
+            inherited_rpath: BTreeMap::new(),
+            loader_rpath: Vec::new(),
         }
     }
 
@@ -414,15 +995,19 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
         }
 
         let identity = crate::os::get_file_inode(full_name).ok();
+        // `RTLD_NODELETE` pins the opened library only, as in glibc.
+        let flags = if self.resolving_root {
+            self.shared.flags
+        } else {
+            self.shared.flags - OpenFlags::RTLD_NODELETE
+        };
         let shortname = self.shared.with_manager_mut(|manager| {
-            reserve_pending(
-                shortname.to_owned(),
-                full_name,
-                identity,
-                self.shared.flags,
-                manager,
-            )
+            reserve_pending(shortname.to_owned(), full_name, identity, flags, manager)
         });
+        if !self.loader_rpath.is_empty() {
+            self.inherited_rpath
+                .insert(shortname.clone(), self.loader_rpath.clone());
+        }
         self.added_names.insert(shortname);
     }
 
@@ -477,7 +1062,7 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
         libs: Vec<String>,
     ) -> Result<ResolvedKey<'bytes, String>> {
         self.resolve_first(libs, |resolver, lib| {
-            resolver.resolve_request(visible, rpath, runpath, &lib, None)
+            resolver.resolve_request(visible, rpath, runpath, None, &lib, None)
         })?
         .ok_or_else(|| find_lib_error("can not resolve linker script".to_string()))
     }
@@ -491,8 +1076,16 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
         bytes: Option<&'bytes [u8]>,
     ) -> Result<ResolvedKey<'bytes, String>> {
         let shortname = path.file_name();
-        if let Some(module) = self.resolve_existing_by_path(path.as_str(), shortname, visible)? {
-            return Ok(module);
+        #[cfg(feature = "std")]
+        if bytes.is_none() && self.builder.is_none() {
+            check_load_root(std::path::Path::new(path.as_str()))?;
+        }
+        match self.resolve_existing_by_path(path.as_str(), shortname, visible) {
+            Ok(Some(module)) => return Ok(module),
+            Ok(None) => {}
+            // An in-memory or built image is not required to exist on disk.
+            Err(_) if bytes.is_some() || self.builder.is_some() => {}
+            Err(e) => return Err(e),
         }
 
         match self.load_candidate(path.as_str(), bytes)? {
@@ -530,8 +1123,22 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
     }
 
     fn load_candidate_file(&self, path: &str) -> Result<CandidateInput<'bytes>> {
+        if let Some((builder, objects)) = self.builder {
+            let bytes = objects.keep(builder(path)?);
+            return self.load_candidate_bytes(path, bytes);
+        }
+        ld_debug!(FILES, trace, "dlopen: Trying file [{}]", path);
         let header = crate::os::read_file_limit(path, 64)?;
         if is_elf_input(&header) {
+            if !is_host_object(&header) {
+                ld_debug!(
+                    FILES,
+                    debug,
+                    "dlopen: Skipping [{}], built for another machine",
+                    path
+                );
+                return Err(find_lib_error(format!("{}: [{}]", WRONG_ARCH, path)));
+            }
             Ok(CandidateInput::Reader(Box::new(ElfFile::from_path(path)?)))
         } else {
             let content = crate::os::read_file(path)?;
@@ -567,11 +1174,13 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
         })
     }
 
+    /// Finds a library by name. `owner` is the path of the library that needs it, if any.
     fn resolve_request(
         &mut self,
         visible: Option<&dyn Fn(&str) -> bool>,
         rpath: &[ElfPath],
         runpath: &[ElfPath],
+        owner: Option<&str>,
         lib_name: &str,
         bytes: Option<&'bytes [u8]>,
     ) -> Result<ResolvedKey<'bytes, String>> {
@@ -600,6 +1209,17 @@ impl<'ctx, 'mgr, 'bytes> LinkResolver<'ctx, 'mgr, 'bytes> {
             return Ok(module);
         }
 
+        if SIBLING_LOOKUP.load(Ordering::Relaxed)
+            && let Some((owner_dir, _)) = owner.and_then(|owner| owner.rsplit_once('/'))
+        {
+            let sibling = ElfPath::from(owner_dir).join(lib_name);
+            match self.resolve_candidate_path(visible, rpath, runpath, &sibling, bytes) {
+                Ok(module) => return Ok(module),
+                Err(err) if should_continue_library_search(&err) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
         if let Some(cached_path) = LD_CACHE
             .as_ref()
             .and_then(|cache| cache.lookup(lib_name))
@@ -640,8 +1260,10 @@ impl<'ctx, 'mgr, 'bytes> KeyResolver<'bytes, String> for LinkResolver<'ctx, 'mgr
         } else {
             None
         };
-        self.resolve_request(None, &[], &[], key, bytes)
-            .map_err(into_linker_error)
+        self.resolving_root = true;
+        let res = self.resolve_request(None, &[], &[], None, key, bytes);
+        self.resolving_root = false;
+        res.map_err(|err| self.shared.fail(err))
     }
 
     fn resolve_dependency(
@@ -657,42 +1279,200 @@ impl<'ctx, 'mgr, 'bytes> KeyResolver<'bytes, String> for LinkResolver<'ctx, 'mgr
             .runpath()
             .map(|r| fixup_rpath(owner_name, r))
             .unwrap_or_default();
+        // As in glibc, `DT_RPATH` is inherited along the chain of loaders, while
+        // `DT_RUNPATH` only applies to its own object. An object with `DT_RUNPATH` searches
+        // no `DT_RPATH` at all, neither its own nor that of its loaders, but the loaders'
+        // entries still reach the dependencies further down the chain.
+        let mut chain = if runpath.is_empty() {
+            rpath.into_vec()
+        } else {
+            Vec::new()
+        };
+        if let Some(inherited) = self.inherited_rpath.get(shortname_from_name(owner_name)) {
+            chain.extend(inherited.iter().cloned());
+        }
+        let search: &[ElfPath] = if runpath.is_empty() { &chain } else { &[] };
         let is_visible = |key: &str| req.is_visible(&key.to_owned());
-        self.resolve_request(Some(&is_visible), &rpath, &runpath, req.needed(), None)
-            .map_err(into_linker_error)
+        self.loader_rpath = chain.clone();
+        let res = self.resolve_request(
+            Some(&is_visible),
+            search,
+            &runpath,
+            Some(owner_name),
+            req.needed(),
+            None,
+        );
+        self.loader_rpath.clear();
+        match res {
+            Err(err @ crate::Error::FindLibError { .. })
+                if LENIENT_MISSING_DEPS.load(Ordering::Relaxed) =>
+            {
+                // The main program is always loaded and global, so standing in for the
+                // missing library adds nothing to the scope of the owner.
+                let Some(main) = self.shared.with_manager(|manager| manager.main_shortname())
+                else {
+                    return Err(self.shared.fail(err));
+                };
+                log::warn!(
+                    "dlopen: [{}] needed by [{}] is missing, continuing: {}",
+                    req.needed(),
+                    owner_name,
+                    err
+                );
+                Ok(ResolvedKey::existing(main))
+            }
+            res => res.map_err(|err| self.shared.fail(err)),
+        }
     }
 }
 
-struct DlopenPlanner<'ctx, 'mgr> {
+struct DlopenPlanner<'ctx, 'mgr, 'h> {
     shared: &'ctx OpenShared<'mgr>,
     relocation_scope: Option<ModuleScope>,
+    /// The relocation scope, kept when a filter in it has to answer for its symbols.
+    filtered_scope: Option<Arc<[LoadedDylib]>>,
+    progress: Option<&'h mut dyn FnMut(Progress<'_>) -> ControlFlow<()>>,
+    resolver: Option<SymbolResolver>,
+    planned: usize,
+    /// The number of libraries in the relocation batch, counted before the first report.
+    total: Option<usize>,
 }
 
-impl<'ctx, 'mgr> DlopenPlanner<'ctx, 'mgr> {
-    fn new(shared: &'ctx OpenShared<'mgr>) -> Self {
+impl<'ctx, 'mgr, 'h> DlopenPlanner<'ctx, 'mgr, 'h> {
+    fn new(
+        shared: &'ctx OpenShared<'mgr>,
+        progress: Option<&'h mut dyn FnMut(Progress<'_>) -> ControlFlow<()>>,
+        resolver: Option<SymbolResolver>,
+    ) -> Self {
         Self {
             shared,
             relocation_scope: None,
+            filtered_scope: None,
+            progress,
+            resolver,
+            planned: 0,
+            total: None,
+        }
+    }
+
+    fn report_progress(
+        &mut self,
+        req: &RelocationRequest<'_, String, ExtraData>,
+    ) -> core::result::Result<(), elf_loader::Error> {
+        let current = self.planned;
+        self.planned += 1;
+        let Some(progress) = self.progress.as_mut() else {
+            return Ok(());
+        };
+        let report = Progress {
+            name: req.key(),
+            current,
+            total: self.total.unwrap_or_default(),
+        };
+        match progress(report) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => {
+                ld_debug!(
+                    RELOC,
+                    info,
+                    "dlopen{}: Load cancelled before relocating [{}]",
+                    ThreadTag,
+                    req.key()
+                );
+                Err(elf_loader::CustomError::Message(
+                    "dlopen cancelled by progress callback".into(),
+                )
+                .into())
+            }
         }
     }
 }
 
-impl RelocationPlanner<String, ExtraData> for DlopenPlanner<'_, '_> {
+impl RelocationPlanner<String, ExtraData> for DlopenPlanner<'_, '_, '_> {
     fn plan(
         &mut self,
         req: &RelocationRequest<'_, String, ExtraData>,
     ) -> core::result::Result<RelocationInputs<ExtraData>, elf_loader::Error> {
         if self.relocation_scope.is_none() {
-            self.relocation_scope = Some(self.shared.prepare_relocation(req.scope()));
+            // The group scope also lists the loaded libraries the new ones depend on.
+            let new_libs = self.shared.with_manager(|manager| {
+                req.scope()
+                    .iter()
+                    .filter_map(|module| module.as_loaded::<ExtraData>())
+                    .filter(|lib| {
+                        !manager
+                            .lookup(lib.shortname())
+                            .is_some_and(|lookup| lookup.is_relocated())
+                    })
+                    .collect::<Vec<_>>()
+            });
+            // As in glibc, only a new mapping is refused; nothing has been relocated yet.
+            if let Some(lib) = new_libs.iter().find(|lib| lib.user_data().is_noopen()) {
+                log::info!("dlopen: Refusing [{}]: marked DF_1_NOOPEN", lib.name());
+                return Err(self.shared.fail(find_lib_error(format!(
+                    "can not dlopen [{}]: DF_1_NOOPEN",
+                    lib.name()
+                ))));
+            }
+            self.total = self.progress.is_some().then_some(new_libs.len());
+            let (scope, filtered) = self.shared.prepare_relocation(req.scope());
+            // The registry lock is released by now, so the callback may call back into the
+            // crate.
+            self.report_progress(req)?;
+            // No relocation of this load has been planned yet, so the libraries loaded
+            // along with a filter bind to its filtees.
+            if let Some(filtered) = &filtered {
+                load_filtees(filtered, self.shared.flags).map_err(|err| self.shared.fail(err))?;
+            }
+            self.relocation_scope = Some(scope);
+            self.filtered_scope = filtered;
+        } else {
+            self.report_progress(req)?;
         }
 
-        log::debug!("Planning relocation for dylib [{}]", req.key());
+        ld_debug!(
+            RELOC,
+            debug,
+            "dlopen{}: Planning relocation for dylib [{}]",
+            ThreadTag,
+            req.key()
+        );
 
         let relocation_scope = self
             .relocation_scope
             .as_ref()
             .expect("Relocation scope must be initialized");
-        let inputs = RelocationInputs::scope(relocation_scope.clone());
+        let mut inputs = RelocationInputs::scope(relocation_scope.clone());
+        // A library's references to its own protected symbols never go to an interposer.
+        let protected = req
+            .scope()
+            .iter()
+            .filter_map(|module| module.as_loaded::<ExtraData>())
+            .find(|lib| lib.shortname() == req.key())
+            .map(protected_definitions)
+            .filter(|protected| !protected.is_empty());
+        let resolver = self.resolver.clone();
+        let filtered = self.filtered_scope.clone();
+        if resolver.is_some() || protected.is_some() || filtered.is_some() {
+            // The lazy fixup keeps this closure, so it owns its own references. The
+            // protected addresses are copied rather than keeping the library alive.
+            inputs = inputs.pre_find_fn(move |name: &str| {
+                resolver
+                    .as_ref()
+                    .and_then(|resolver| resolver(name))
+                    .or_else(|| {
+                        protected
+                            .as_ref()
+                            .and_then(|protected| protected.get(name))
+                            .map(|&addr| addr as *const ())
+                    })
+                    .or_else(|| {
+                        filtered
+                            .as_ref()
+                            .and_then(|scope| filtered_scope_find(scope, name))
+                    })
+            });
+        }
         if self.shared.flags.is_now() {
             Ok(inputs.eager())
         } else if self.shared.flags.is_lazy() {
@@ -707,8 +1487,8 @@ fn link_root<'mgr, 'bytes>(
     mut ctx: OpenContext<'mgr>,
     root_request: &str,
     root: LinkRoot<'bytes>,
+    hooks: OpenHooks<'_>,
 ) -> Result<ElfLibrary> {
-    #[cfg(not(feature = "std"))]
     if let LinkRoot::Mapped { key, raw } = &root {
         if let Some(lib) = ctx.shared.wait_for_library(None, key) {
             return Ok(ctx.finish_existing(raw.name(), lib));
@@ -716,26 +1496,40 @@ fn link_root<'mgr, 'bytes>(
         ctx.reserve_pending(key, raw.name());
     }
 
+    let OpenHooks {
+        progress,
+        builder,
+        resolver,
+        defer_init,
+    } = hooks;
+    let objects = ObjectArena::default();
     let key_resolver = LinkResolver::new(
         &ctx.shared,
         &mut ctx.added_names,
         root_request,
         root.bytes(),
+        builder.map(|builder| (builder, &objects)),
     );
     let visible_modules = DlopenVisible::new(&ctx.shared);
     let mut link_ctx = LinkContext::new();
-    let relocation_planner = DlopenPlanner::new(&ctx.shared);
+    let relocation_planner = DlopenPlanner::new(&ctx.shared, progress, resolver.clone());
     let mut linker = Linker::<String, ()>::new()
-        .map_loader(|_| new_loader())
+        .map_loader(move |_| new_dlopen_loader(defer_init))
         .visible_modules(visible_modules)
         .resolver(key_resolver)
         .planner(relocation_planner);
     let load_result = match root {
-        LinkRoot::Load { key, .. } => linker.load(&mut link_ctx, key)?,
-        #[cfg(not(feature = "std"))]
-        LinkRoot::Mapped { key, raw } => linker.load_mapped_root(&mut link_ctx, key, raw)?,
+        LinkRoot::Load { key, .. } => linker.load(&mut link_ctx, key),
+        LinkRoot::Mapped { key, raw } => linker.load_mapped_root(&mut link_ctx, key, raw),
     };
     drop(linker);
+    let load_result = match load_result {
+        Ok(load_result) => load_result,
+        Err(err) => {
+            let err = ctx.shared.failure.take().unwrap_or_else(|| err.into());
+            return Err(ctx.describe_unresolved(resolver.as_ref(), err));
+        }
+    };
 
     let root_shortname = load_result.root().shortname().to_owned();
     ctx.complete_relocation(&link_ctx, load_result.committed().iter().copied());
@@ -746,17 +1540,72 @@ fn link_root<'mgr, 'bytes>(
     Ok(ctx.finish(deps))
 }
 
-fn dlopen_impl(path: &str, flags: OpenFlags, bytes: Option<&[u8]>) -> Result<ElfLibrary> {
+/// Opens the filtees of every filter library in `scope` that has none yet.
+///
+/// The relocation planner calls this once the registry lock is released and before the
+/// first relocation of the load, so every library in the load resolves the filtered
+/// symbols through the filtees. A missing `DT_FILTER` filtee fails the load; a missing
+/// `DT_AUXILIARY` one is skipped.
+fn load_filtees(scope: &[LoadedDylib], flags: OpenFlags) -> Result<()> {
+    let flags = flags - OpenFlags::RTLD_NOLOAD;
+    for filter in scope.iter().filter(|dep| dep.user_data().is_filter()) {
+        let data = filter.user_data();
+        if data.filtees.is_completed() {
+            continue;
+        }
+        let origin = filter.name().rsplit_once('/').map_or(".", |(dir, _)| dir);
+        let required = data.filters.iter().map(|name| (name, true));
+        let auxiliary = data.auxiliaries.iter().map(|name| (name, false));
+        let mut filtees = Vec::new();
+        for (name, required) in required.chain(auxiliary) {
+            let name = name.replace("${ORIGIN}", origin).replace("$ORIGIN", origin);
+            match ElfLibrary::dlopen(name.as_str(), flags) {
+                Ok(filtee) => filtees.push(filtee),
+                Err(err) if required => {
+                    return Err(find_lib_error(format!(
+                        "can not load filtee [{}] of [{}]: {}",
+                        name,
+                        filter.name(),
+                        err
+                    )));
+                }
+                Err(err) => ld_debug!(
+                    LIBS,
+                    info,
+                    "dlopen: Skipping auxiliary filtee [{}] of [{}]: {}",
+                    name,
+                    filter.name(),
+                    err
+                ),
+            }
+        }
+        data.filtees.call_once(|| filtees.into_boxed_slice());
+    }
+    Ok(())
+}
+
+fn dlopen_impl(
+    path: &str,
+    flags: OpenFlags,
+    bytes: Option<&[u8]>,
+    hooks: OpenHooks<'_>,
+) -> Result<ElfLibrary> {
+    check_flags(flags)?;
     let mut ctx = OpenContext::new(flags);
 
-    log::info!(
-        "dlopen: Try to open [{}] with [{:?}] ",
+    ld_debug!(
+        LIBS,
+        info,
+        "dlopen{}: Try to open [{}] with [{:?}] ",
+        ThreadTag,
         path,
         ctx.shared.flags
     );
 
-    if let Some(lib) = ctx.try_existing(path)? {
-        return Ok(lib);
+    let defer_init = hooks.defer_init;
+    if let Some(lib) = ctx.try_existing(path, bytes.is_some() || hooks.builder.is_some())? {
+        drop(ctx);
+        return Ok(run_pending_constructors(lib, defer_init));
     }
 
     if ctx.shared.flags.is_noload() {
@@ -770,26 +1619,65 @@ fn dlopen_impl(path: &str, flags: OpenFlags, bytes: Option<&[u8]>) -> Result<Elf
             key: path.to_owned(),
             bytes,
         },
+        hooks,
     )
+    .map(|lib| run_pending_constructors(lib, defer_init))
+}
+
+/// Runs the constructors an earlier [`ElfLibrary::dlopen_no_init`] left pending in the
+/// searchlist of `lib`, unless this load defers constructors as well.
+///
+/// This must run after the registry lock is released, since constructors may `dlopen`.
+fn run_pending_constructors(lib: ElfLibrary, defer_init: bool) -> ElfLibrary {
+    if !defer_init {
+        lib.run_constructors();
+    }
+    lib
+}
+
+/// Rejects flag combinations that have no meaning, before `LD_BIND_NOW` adds to them.
+///
+/// Unlike glibc, which binds `RTLD_LAZY | RTLD_NOW` as `RTLD_NOW`, both modes together are
+/// refused, reported like any other library that can not be opened.
+fn check_flags(flags: OpenFlags) -> Result<()> {
+    if flags.is_lazy() && flags.is_now() {
+        return Err(find_lib_error(
+            "dlopen: RTLD_LAZY and RTLD_NOW are mutually exclusive",
+        ));
+    }
+    let unknown = flags.bits() & !OpenFlags::all().bits();
+    if unknown != 0 {
+        log::warn!("dlopen: ignoring unknown flag bits [{:#x}]", unknown);
+    }
+    Ok(())
 }
 
-#[cfg(not(feature = "std"))]
 pub(crate) fn dlopen_mapped_root(
     root_request: &str,
     raw: ElfDylib,
     flags: OpenFlags,
 ) -> Result<ElfLibrary> {
+    check_flags(flags)?;
     let root_key = shortname_from_name(raw.name()).to_owned();
     let ctx = OpenContext::new(flags);
 
-    log::info!(
-        "dlopen: Link mapped root [{}] as [{}] with [{:?}]",
+    ld_debug!(
+        LIBS,
+        info,
+        "dlopen{}: Link mapped root [{}] as [{}] with [{:?}]",
+        ThreadTag,
         root_request,
         root_key,
         ctx.shared.flags
     );
 
-    link_root(ctx, root_request, LinkRoot::Mapped { key: root_key, raw })
+    link_root(
+        ctx,
+        root_request,
+        LinkRoot::Mapped { key: root_key, raw },
+        OpenHooks::default(),
+    )
+    .map(|lib| run_pending_constructors(lib, false))
 }
 
 static LD_LIBRARY_PATH: Lazy<Box<[ElfPath]>> = Lazy::new(|| {
@@ -873,32 +1761,34 @@ fn should_continue_library_search(err: &crate::error::Error) -> bool {
                 || msg.contains("ENOENT")
                 || msg.contains("Failed to open file")
         }
+        // Multiarch systems keep libraries of the same name for other machines around.
+        crate::error::Error::FindLibError { msg } => msg.starts_with(WRONG_ARCH),
         _ => false,
     }
 }
 
-#[inline]
-fn is_elf_input(bytes: &[u8]) -> bool {
-    bytes.starts_with(b"\x7fELF")
-}
-
 /// # Safety
 /// It is the same as `dlopen`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn dlopen(filename: *const c_char, flags: c_int) -> *const c_void {
-    let lib = if filename.is_null() {
-        ElfLibrary::this()
-    } else {
-        let flags = OpenFlags::from_bits_retain(flags as _);
-        let filename = unsafe { CStr::from_ptr(filename) };
-        let Ok(path) = filename.to_str() else {
-            return core::ptr::null();
-        };
-        if let Ok(lib) = ElfLibrary::dlopen(path, flags) {
-            lib
+    super::catch_panic("dlopen", core::ptr::null(), || {
+        let lib = if filename.is_null() {
+            ElfLibrary::this()
         } else {
-            return core::ptr::null();
-        }
-    };
-    Box::into_raw(Box::new(lib)) as _
+            let flags = OpenFlags::from_bits_retain(flags as _);
+            let filename = unsafe { CStr::from_ptr(filename) };
+            let Ok(path) = filename.to_str() else {
+                super::set_dlerror("dlopen: file name is not valid UTF-8");
+                return core::ptr::null();
+            };
+            match ElfLibrary::dlopen(path, flags) {
+                Ok(lib) => lib,
+                Err(err) => {
+                    super::set_dlerror(err);
+                    return core::ptr::null();
+                }
+            }
+        };
+        super::into_handle(lib)
+    })
 }
@@ -1,5 +1,7 @@
-use crate::core_impl::{find_symbol, global_find, next_find};
-use crate::{Result, Symbol, error::find_symbol_error};
+use crate::core_impl::{
+    find_symbol, find_symbol_bytes, global_find, global_find_bytes, next_find, next_find_bytes,
+};
+use crate::{Result, Symbol, error::find_symbol_error, utils::ld_debug::ld_debug};
 use core::{
     ffi::{CStr, c_char, c_void},
     ptr::null,
@@ -9,32 +11,57 @@ use core::{
 /// It is the same as `dlsym`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn dlsym(handle: *const c_void, symbol_name: *const c_char) -> *const c_void {
-    const RTLD_DEFAULT: usize = 0;
-    const RTLD_NEXT: usize = usize::MAX;
-    let value = handle as usize;
-    let name = match unsafe { CStr::from_ptr(symbol_name).to_str() } {
-        Ok(name) => name,
-        Err(_) => return null(),
-    };
+    super::catch_panic("dlsym", null(), || {
+        const RTLD_DEFAULT: usize = 0;
+        const RTLD_NEXT: usize = usize::MAX;
+        let value = handle as usize;
+        let name = unsafe { CStr::from_ptr(symbol_name) };
+        // The few names that are not UTF-8 are matched byte for byte.
+        let Ok(name) = name.to_str() else {
+            let name = name.to_bytes();
+            let sym = if value == RTLD_DEFAULT {
+                global_find_bytes(name)
+            } else if value == RTLD_NEXT {
+                unsafe { next_find_bytes(caller_addr(), name) }
+            } else {
+                let lib = unsafe { &*(handle as *const crate::ElfLibrary) };
+                find_symbol_bytes(lib.deps.as_deref().unwrap(), name)
+            };
+            return sym.unwrap_or(null()).cast();
+        };
 
-    let sym = if value == RTLD_DEFAULT {
-        log::info!("dlsym: Use RTLD_DEFAULT flag to find symbol [{}]", name);
-        dlsym_default::<()>(name).ok().map(|s| s.into_raw())
-    } else if value == RTLD_NEXT {
-        log::info!("dlsym: Use RTLD_NEXT flag to find symbol [{}]", name);
-        unsafe { dlsym_next::<()>(name).ok().map(|s| s.into_raw()) }
-    } else {
-        let lib = unsafe { &*(handle as *const crate::ElfLibrary) };
-        let libs = lib.deps.as_ref().unwrap();
-        let symbol = find_symbol::<()>(&libs[..], name)
-            .ok()
-            .map(|sym| sym.into_raw());
-        symbol
-    };
-    sym.unwrap_or(null()).cast()
+        let sym = if value == RTLD_DEFAULT {
+            ld_debug!(
+                SYMBOLS,
+                info,
+                "dlsym: Use RTLD_DEFAULT flag to find symbol [{}]",
+                name
+            );
+            dlsym_default::<()>(name).ok().map(|s| s.into_raw())
+        } else if value == RTLD_NEXT {
+            ld_debug!(
+                SYMBOLS,
+                info,
+                "dlsym: Use RTLD_NEXT flag to find symbol [{}]",
+                name
+            );
+            unsafe { dlsym_next::<()>(name).ok().map(|s| s.into_raw()) }
+        } else {
+            let lib = unsafe { &*(handle as *const crate::ElfLibrary) };
+            let libs = lib.deps.as_ref().unwrap();
+            let symbol = find_symbol::<()>(&libs[..], name)
+                .ok()
+                .map(|sym| sym.into_raw());
+            symbol
+        };
+        sym.unwrap_or(null()).cast()
+    })
 }
 
 /// Find a symbol in the global search scope.
+///
+/// The main program is searched first, then the libraries loaded at startup, then every
+/// `RTLD_GLOBAL` library in the order it was loaded or promoted. The first definition wins.
 #[inline]
 pub fn dlsym_default<T>(name: &str) -> Result<Symbol<'static, T>> {
     unsafe { global_find(name) }
@@ -47,7 +74,14 @@ pub fn dlsym_default<T>(name: &str) -> Result<Symbol<'static, T>> {
 /// This function uses inline assembly to determine the caller's address.
 #[inline(always)]
 pub unsafe fn dlsym_next<T>(name: &str) -> Result<Symbol<'static, T>> {
-    let caller = unsafe {
+    unsafe { next_find(caller_addr(), name) }
+        .ok_or_else(|| find_symbol_error(alloc::format!("can not find symbol:{}", name)))
+}
+
+/// The return address of the function this is inlined into, i.e. an address in its caller.
+#[inline(always)]
+unsafe fn caller_addr() -> usize {
+    unsafe {
         let ra: usize;
         #[cfg(target_arch = "x86_64")]
         core::arch::asm!(
@@ -74,7 +108,52 @@ pub unsafe fn dlsym_next<T>(name: &str) -> Result<Symbol<'static, T>> {
         )))]
         let ra = 0;
         ra
-    };
-    unsafe { next_find(caller, name) }
-        .ok_or_else(|| find_symbol_error(alloc::format!("can not find symbol:{}", name)))
+    }
+}
+
+/// The address of a [`Symbol`], for checking which definition a lookup bound to.
+///
+/// [`Symbol`] is defined by `elf_loader`, so it cannot implement `PartialEq` here. Converting
+/// it into a `SymbolAddr` only keeps the address; nothing is read through it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SymbolAddr(*const ());
+
+impl SymbolAddr {
+    #[inline]
+    pub(crate) fn from_raw(addr: *const ()) -> Self {
+        SymbolAddr(addr)
+    }
+
+    /// The address as a pointer.
+    #[inline]
+    pub fn as_ptr(self) -> *const () {
+        self.0
+    }
+}
+
+impl<T> From<Symbol<'_, T>> for SymbolAddr {
+    #[inline]
+    fn from(sym: Symbol<'_, T>) -> Self {
+        SymbolAddr(sym.into_raw())
+    }
+}
+
+impl PartialEq<*const ()> for SymbolAddr {
+    #[inline]
+    fn eq(&self, other: &*const ()) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<usize> for SymbolAddr {
+    #[inline]
+    fn eq(&self, other: &usize) -> bool {
+        self.0 as usize == *other
+    }
+}
+
+impl core::fmt::Debug for SymbolAddr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SymbolAddr({:p})", self.0)
+    }
 }
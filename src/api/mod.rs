@@ -1,29 +1,114 @@
 //! c interface
+//!
+//! With `std`, a panic inside any of these functions is caught and turned into the
+//! function's usual failure return, with the reason available from `dlerror`.
 
 mod dl_find_object;
 pub(crate) mod dl_iterate_phdr;
 pub(crate) mod dladdr;
+mod dlinfo;
 pub(crate) mod dlopen;
 pub mod dlsym;
 
-use alloc::boxed::Box;
-use core::ffi::{c_int, c_void};
+use crate::ElfLibrary;
+use alloc::{boxed::Box, collections::BTreeSet, ffi::CString, string::ToString};
+use core::{
+    ffi::{c_char, c_int, c_void},
+    ptr::null,
+};
+use spin::Mutex;
 
 pub use self::dl_find_object::{dl_find_dso_for_object, dl_find_object};
-pub use self::dl_iterate_phdr::dl_iterate_phdr;
+pub use self::dl_iterate_phdr::{CDlPhdrInfo, dl_iterate_phdr};
 pub use self::dladdr::dladdr;
+pub use self::dlinfo::dlinfo;
 pub use self::dlopen::dlopen;
 pub use self::dlsym::dlsym;
 
+/// Addresses of the handles returned by `dlopen` that have not been closed yet.
+static HANDLES: Mutex<BTreeSet<usize>> = Mutex::new(BTreeSet::new());
+
+/// Turns a library into a handle for the C interface.
+pub(crate) fn into_handle(lib: ElfLibrary) -> *const c_void {
+    let handle = Box::into_raw(Box::new(lib));
+    HANDLES.lock().insert(handle as usize);
+    handle as _
+}
+
+/// Whether `handle` was returned by `dlopen` and has not been closed yet.
+pub(crate) fn is_live_handle(handle: *const c_void) -> bool {
+    HANDLES.lock().contains(&(handle as usize))
+}
+
+/// The error reported by the next `dlerror` call, and the one it last returned.
+struct DlError {
+    pending: Option<CString>,
+    reported: Option<CString>,
+}
+
+static DLERROR: Mutex<DlError> = Mutex::new(DlError {
+    pending: None,
+    reported: None,
+});
+
+/// Records the error message returned by the next `dlerror` call.
+pub(crate) fn set_dlerror(msg: impl ToString) {
+    DLERROR.lock().pending = CString::new(msg.to_string()).ok();
+}
+
+/// Runs the body of the C entry point `name`, returning `on_panic` if it panics.
+///
+/// A panic unwinding out of an `extern "C"` function aborts the process, so with `std` it is
+/// caught here and reported through `dlerror` instead. Without `std` nothing unwinds and `f`
+/// is simply called.
+#[inline]
+pub(crate) fn catch_panic<R>(name: &str, on_panic: R, f: impl FnOnce() -> R) -> R {
+    #[cfg(feature = "std")]
+    {
+        match std::panic::catch_unwind(core::panic::AssertUnwindSafe(f)) {
+            Ok(ret) => ret,
+            Err(_) => {
+                log::error!("{}: Caught a panic, failing the call", name);
+                set_dlerror(alloc::format!("{}: internal error", name));
+                on_panic
+            }
+        }
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = (name, on_panic);
+        f()
+    }
+}
+
+/// # Safety
+/// It is the same as `dlerror`.
+///
+/// Unlike glibc, the error state is shared by all threads. The returned string stays valid
+/// until the next call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn dlerror() -> *const c_char {
+    let mut error = DLERROR.lock();
+    error.reported = error.pending.take();
+    error.reported.as_ref().map_or(null(), |msg| msg.as_ptr())
+}
+
 /// # Safety
 /// It is the same as `dlclose`.
+///
+/// Returns nonzero if `handle` is null or not a live handle returned by `dlopen`, e.g.
+/// because it was already closed. Closing an `RTLD_NODELETE` library succeeds and
+/// returns 0, but the library stays mapped.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn dlclose(handle: *const c_void) -> c_int {
-    if handle.is_null() {
-        return 0;
-    }
-    let lib = unsafe { Box::from_raw(handle as *mut crate::ElfLibrary) };
-    let shortname = lib.shortname();
-    log::info!("dlclose: Closing [{}]", shortname);
-    0
+    catch_panic("dlclose", -1, || {
+        if !HANDLES.lock().remove(&(handle as usize)) {
+            log::warn!("dlclose: Invalid handle [{:p}]", handle);
+            set_dlerror("dlclose: invalid handle");
+            return -1;
+        }
+        let lib = unsafe { Box::from_raw(handle as *mut ElfLibrary) };
+        log::info!("dlclose: Closing [{}]", lib.shortname());
+        0
+    })
 }
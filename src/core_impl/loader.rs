@@ -1,9 +1,24 @@
-use super::types::{ARGC, ARGV, ENVP, ExtraData, LinkMap};
-use crate::utils::debug::add_debug_link_map;
-use crate::{OpenFlags, Result, error::find_symbol_error};
+use super::register::global_find;
+use super::types::{ARGC, ARGV, ENVP, ExtraData, LinkMap, SymbolCache};
+use crate::utils::{debug::add_debug_link_map, ld_debug::ld_debug, trace_resolution};
+use crate::{
+    OpenFlags, Result, SymbolAddr,
+    abi::{
+        dynamic::{
+            DT_AUXILIARY, DT_FILTER, DT_FINI, DT_FINI_ARRAY, DT_FINI_ARRAYSZ, DT_FLAGS_1, DT_INIT,
+            DT_INIT_ARRAY, DT_INIT_ARRAYSZ, DT_PLTREL, DT_PLTRELSZ, DT_RELA, DT_RELAENT, DT_RELASZ,
+            DT_RELENT, DT_RELR, DT_RELRSZ, DT_RELSZ, DT_SONAME,
+        },
+        note::NT_GNU_BUILD_ID,
+        segment::{PF_R, PF_W, PF_X},
+        symbol::{STB_GLOBAL, STT_GNU_IFUNC, STT_TLS, STV_MASK, STV_PROTECTED},
+    },
+    error::find_symbol_error,
+};
 use alloc::{
     borrow::ToOwned,
     boxed::Box,
+    collections::BTreeMap,
     ffi::CString,
     format,
     string::{String, ToString},
@@ -11,25 +26,33 @@ use alloc::{
     vec::Vec,
 };
 use core::{
-    ffi::{c_char, c_int},
+    ffi::{CStr, c_char, c_int},
     fmt::Debug,
+    ops::Range,
     ptr::null,
+    sync::atomic::{AtomicBool, Ordering},
 };
 use elf_loader::{
     Loader,
-    elf::{ElfDyn, ElfPhdr, ElfProgramType, Lifecycle},
+    elf::{ElfDyn, ElfDynamicTag, ElfPhdr, ElfProgramType, ElfRel, ElfRela, Lifecycle},
     image::{LoadedCore, RawDynamic, Symbol},
+    tls::TlsResolver,
 };
+use spin::Mutex;
 
 pub(crate) type ElfDylib = RawDynamic<ExtraData>;
 pub(crate) type LoadedDylib = LoadedCore<ExtraData>;
-pub(crate) type RuntimeLoader =
-    Loader<elf_loader::os::DefaultMmap, (), ExtraData, ActiveTlsResolver>;
+pub(crate) type RuntimeLoader = Loader<ActiveMmap, (), ExtraData, ActiveTlsResolver>;
+
+#[cfg(feature = "noexec-fallback")]
+use crate::os::NoexecMmap as ActiveMmap;
+#[cfg(not(feature = "noexec-fallback"))]
+use elf_loader::os::DefaultMmap as ActiveMmap;
 
 #[cfg(not(feature = "std"))]
-use crate::rtld::ActiveTlsResolver;
+pub(crate) use crate::rtld::ActiveTlsResolver;
 #[cfg(feature = "std")]
-use elf_loader::tls::DefaultTlsResolver as ActiveTlsResolver;
+pub(crate) use elf_loader::tls::DefaultTlsResolver as ActiveTlsResolver;
 
 /// Searches for a symbol in a list of relocated libraries.
 ///
@@ -39,36 +62,289 @@ pub(crate) fn find_symbol<'lib, T>(
     libs: &'lib [LoadedDylib],
     name: &str,
 ) -> Result<Symbol<'lib, T>> {
-    log::info!("Get the symbol [{}] in [{}]", name, libs[0].name());
+    ld_debug!(
+        SYMBOLS,
+        info,
+        "Get the symbol [{}] in [{}]",
+        name,
+        libs[0].name()
+    );
     libs.iter()
-        .find_map(|lib| unsafe { lib.get::<T>(name) })
+        .find_map(|lib| {
+            let sym = unsafe { filtered_get::<T>(lib, name) }?;
+            trace_resolution!(name, lib, "local");
+            Some(sym)
+        })
         .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
 }
 
+/// Looks up a definition of `name` in `lib` itself.
+#[inline]
+unsafe fn own_get<'lib, T>(lib: &'lib LoadedDylib, name: &str) -> Option<Symbol<'lib, T>> {
+    let sym = unsafe { lib.get::<T>(name) }?;
+    #[cfg(feature = "version")]
+    let sym = unsafe { prefer_default_version(lib, name, sym) }?;
+    Some(sym)
+}
+
+/// Looks up `name` in `lib` the way a filter library answers for its symbols.
+///
+/// The loaded filtees are asked first. A `DT_FILTER` library never provides its own
+/// definitions, a `DT_AUXILIARY` one does when no filtee has the symbol. Only the filtees'
+/// own definitions count, so filters that name each other cannot loop.
+pub(crate) unsafe fn filtered_get<'lib, T>(
+    lib: &'lib LoadedDylib,
+    name: &str,
+) -> Option<Symbol<'lib, T>> {
+    unsafe { filtered_lookup(lib, name) }.map(|(_, sym)| sym)
+}
+
+/// Like [`filtered_get`], also returning the library whose definition answered: `lib`
+/// itself or one of its filtees.
+unsafe fn filtered_lookup<'lib, T>(
+    lib: &'lib LoadedDylib,
+    name: &str,
+) -> Option<(&'lib LoadedDylib, Symbol<'lib, T>)> {
+    let data = lib.user_data();
+    if !data.is_filter() {
+        return unsafe { own_get(lib, name) }.map(|sym| (lib, sym));
+    }
+    // Until the loading `dlopen` has opened the filtees, the library answers for itself.
+    let Some(filtees) = data.filtees.get() else {
+        return unsafe { own_get(lib, name) }.map(|sym| (lib, sym));
+    };
+    if let Some(found) = filtees.iter().find_map(|filtee| {
+        unsafe { own_get::<T>(&filtee.inner, name) }.map(|sym| (&filtee.inner, sym))
+    }) {
+        trace_resolution!(name, lib, "filtee");
+        return Some(found);
+    }
+    if data.filters.is_empty() {
+        unsafe { own_get(lib, name) }.map(|sym| (lib, sym))
+    } else {
+        None
+    }
+}
+
+/// Resolves `name` for a relocation against `scope` when a filter library would answer.
+///
+/// Returns `None` when the first library of `scope` that has `name` is not a filter, leaving
+/// the lookup to the normal scope walk.
+pub(crate) fn filtered_scope_find(scope: &[LoadedDylib], name: &str) -> Option<*const ()> {
+    for lib in scope {
+        if lib.user_data().is_filter() {
+            if let Some(sym) = unsafe { filtered_get::<()>(lib, name) } {
+                return Some(sym.into_raw());
+            }
+        } else if unsafe { lib.get::<()>(name) }.is_some() {
+            return None;
+        }
+    }
+    None
+}
+
+/// Lists the undefined entries of the dynamic symbol table of `lib` with global binding.
+pub(crate) fn strong_undefined_symbols(lib: &LoadedDylib) -> Vec<String> {
+    let symtab = lib.symtab();
+    (1..symtab.count_syms())
+        .filter_map(|i| {
+            let (sym, syminfo) = symtab.symbol_idx(i);
+            if !sym.is_undef() || sym.st_bind() != STB_GLOBAL {
+                return None;
+            }
+            let name = syminfo.cname()?.to_str().ok()?;
+            (!name.is_empty()).then(|| name.to_owned())
+        })
+        .collect()
+}
+
+/// Collects the addresses of the `STV_PROTECTED` symbols `lib` defines, by name.
+///
+/// References from `lib` to these bind to its own definitions even when an earlier library
+/// in the scope interposes the name. `STT_GNU_IFUNC` symbols are left to the normal lookup,
+/// which runs their resolver, and so are `STT_TLS` ones, whose value is an offset into the
+/// thread-local block rather than an address.
+pub(crate) fn protected_definitions(lib: &LoadedDylib) -> BTreeMap<String, usize> {
+    let symtab = lib.symtab();
+    (0..symtab.count_syms())
+        .filter_map(|i| {
+            let (sym, syminfo) = symtab.symbol_idx(i);
+            if sym.st_value() == 0
+                || !sym.is_ok_bind()
+                || sym.st_other() & STV_MASK != STV_PROTECTED
+                || matches!(sym.st_type(), STT_GNU_IFUNC | STT_TLS)
+            {
+                return None;
+            }
+            let name = syminfo.cname()?.to_str().ok()?;
+            Some((name.to_owned(), lib.base() + sym.st_value()))
+        })
+        .collect()
+}
+
+/// Searches `libs` in order for a symbol whose name is the raw bytes `name`.
+///
+/// ELF symbol names need not be UTF-8, which lookups through `&str` cannot express. This
+/// walks the symbol tables instead of using the hash tables, so it is only meant for the
+/// names [`find_symbol`] cannot take. `STT_TLS` and `STT_GNU_IFUNC` symbols are skipped:
+/// their value is a thread-local offset or a resolver rather than the address asked for.
+pub(crate) fn find_symbol_bytes<'a>(
+    libs: impl IntoIterator<Item = &'a LoadedDylib>,
+    name: &[u8],
+) -> Option<*const ()> {
+    libs.into_iter().find_map(|lib| {
+        let symtab = lib.symtab();
+        (0..symtab.count_syms()).find_map(|i| {
+            let (sym, syminfo) = symtab.symbol_idx(i);
+            (sym.st_value() != 0
+                && sym.is_ok_bind()
+                && !matches!(sym.st_type(), STT_GNU_IFUNC | STT_TLS)
+                && syminfo
+                    .cname()
+                    .is_some_and(|cname| cname.to_bytes() == name))
+            .then(|| (lib.base() + sym.st_value()) as *const ())
+        })
+    })
+}
+
 pub(crate) fn new_loader() -> RuntimeLoader {
+    build_loader(false)
+}
+
+/// Creates a loader for `dlopen`.
+///
+/// With `defer_init`, constructors are left for [`ElfLibrary::run_constructors`].
+pub(crate) fn new_dlopen_loader(defer_init: bool) -> RuntimeLoader {
+    build_loader(defer_init)
+}
+
+type InitFn = unsafe extern "C" fn(c_int, *const *mut c_char, *const *mut c_char);
+type FiniFn = unsafe extern "C" fn();
+
+/// Calls an initialization function with the arguments glibc passes to them.
+unsafe fn call_init(init: InitFn) {
+    let argc = unsafe { *core::ptr::addr_of!(ARGC) };
+    let argv = unsafe { *core::ptr::addr_of!(ARGV) };
+    let envp = unsafe { *core::ptr::addr_of!(ENVP) as *const *mut c_char };
+    #[cfg(feature = "init-watchdog")]
+    let _watchdog = crate::utils::watchdog::watch(init as usize);
+    unsafe { init(argc as c_int, argv, envp) };
+}
+
+fn build_loader(defer_init: bool) -> RuntimeLoader {
     Loader::new()
         .with_tls_resolver::<ActiveTlsResolver>()
-        .with_dynamic_initializer::<ExtraData>(|raw| {
+        .with_dynamic_initializer::<ExtraData>(move |raw| {
             let file_path = raw.name().contains('/').then(|| raw.name().to_owned());
             finalize_raw_dylib(raw, file_path.as_deref());
+            if let Some(data) = raw.user_data_mut() {
+                data.init_deferred = AtomicBool::new(defer_init);
+            }
             Ok(())
         })
-        .with_init(|ctx: &Lifecycle| {
-            let argc = unsafe { *core::ptr::addr_of!(ARGC) };
-            let argv = unsafe { *core::ptr::addr_of!(ARGV) };
-            let envp = unsafe { *core::ptr::addr_of!(ENVP) as *const *mut c_char };
-            type InitFn = unsafe extern "C" fn(c_int, *const *mut c_char, *const *mut c_char);
+        .with_init(move |ctx: &Lifecycle| {
+            if defer_init {
+                return;
+            }
             if let Some(init) = ctx.func() {
-                let init: InitFn = unsafe { core::mem::transmute(init) };
-                unsafe { init(argc as c_int, argv, envp) };
+                unsafe { call_init(core::mem::transmute(init)) };
             }
             if let Some(init_array) = ctx.func_array() {
                 for &f in init_array {
-                    let f: InitFn = unsafe { core::mem::transmute(f) };
-                    unsafe { f(argc as c_int, argv, envp) };
+                    unsafe { call_init(core::mem::transmute(f)) };
                 }
             }
         })
+        // The hook is not told which library it runs for, so it can not see whether the
+        // constructors ran or the destructors already did. Destructors are left to
+        // `run_fini`, which is called where a library is released; one dropped by a failed
+        // load is unmapped without them.
+        .with_fini(|_: &Lifecycle| {})
+}
+
+/// Runs `DT_INIT` and then `DT_INIT_ARRAY` of a library whose constructors were deferred.
+unsafe fn run_deferred_init(lib: &LoadedDylib) {
+    let base = lib.base();
+    let mut dynamic = dynamic_table(lib);
+    if dynamic.is_null() {
+        return;
+    }
+    let (mut init, mut init_array, mut init_arraysz) = (None, None, 0);
+    unsafe {
+        while (*dynamic).tag() != ElfDynamicTag::NULL {
+            let value = (*dynamic).value();
+            match (*dynamic).tag().raw() as i64 {
+                DT_INIT => init = Some(dynamic_addr(base, value)),
+                DT_INIT_ARRAY => init_array = Some(dynamic_addr(base, value)),
+                DT_INIT_ARRAYSZ => init_arraysz = value,
+                _ => {}
+            }
+            dynamic = dynamic.add(1);
+        }
+    }
+    log::info!("Running deferred constructors of [{}]", lib.name());
+    if let Some(init) = init {
+        unsafe { call_init(core::mem::transmute::<usize, InitFn>(init)) };
+    }
+    if let Some(init_array) = init_array {
+        let funcs = unsafe {
+            core::slice::from_raw_parts(
+                init_array as *const usize,
+                init_arraysz / size_of::<usize>(),
+            )
+        };
+        // Entries of 0 and -1 are placeholders that glibc skips as well.
+        for &f in funcs.iter().filter(|&&f| f != 0 && f != usize::MAX) {
+            unsafe { call_init(core::mem::transmute::<usize, InitFn>(f)) };
+        }
+    }
+}
+
+/// Runs `DT_FINI_ARRAY` in reverse and then `DT_FINI` of a library.
+///
+/// They run at most once, and not at all if the constructors were deferred and never ran.
+/// The host dynamic linker runs the destructors of the libraries it mapped.
+pub(crate) unsafe fn run_fini(lib: &LoadedDylib) {
+    let data = lib.user_data();
+    if data.host
+        || data.init_deferred.load(Ordering::Acquire)
+        || data.fini_done.swap(true, Ordering::AcqRel)
+    {
+        return;
+    }
+    let base = lib.base();
+    let mut dynamic = dynamic_table(lib);
+    if dynamic.is_null() {
+        return;
+    }
+    let (mut fini, mut fini_array, mut fini_arraysz) = (None, None, 0);
+    unsafe {
+        while (*dynamic).tag() != ElfDynamicTag::NULL {
+            let value = (*dynamic).value();
+            match (*dynamic).tag().raw() as i64 {
+                DT_FINI => fini = Some(dynamic_addr(base, value)),
+                DT_FINI_ARRAY => fini_array = Some(dynamic_addr(base, value)),
+                DT_FINI_ARRAYSZ => fini_arraysz = value,
+                _ => {}
+            }
+            dynamic = dynamic.add(1);
+        }
+    }
+    log::info!("Running destructors of [{}]", lib.name());
+    if let Some(fini_array) = fini_array {
+        let funcs = unsafe {
+            core::slice::from_raw_parts(
+                fini_array as *const usize,
+                fini_arraysz / size_of::<usize>(),
+            )
+        };
+        for &f in funcs.iter().rev().filter(|&&f| f != 0 && f != usize::MAX) {
+            unsafe { core::mem::transmute::<usize, FiniFn>(f)() };
+        }
+    }
+    if let Some(fini) = fini {
+        unsafe { core::mem::transmute::<usize, FiniFn>(fini)() };
+    }
 }
 
 pub(crate) fn finalize_raw_dylib(dylib: &mut ElfDylib, file_path: Option<&str>) {
@@ -86,6 +362,7 @@ pub(crate) fn finalize_raw_dylib(dylib: &mut ElfDylib, file_path: Option<&str>)
         .find(|p: &&ElfPhdr| p.program_type() == ElfProgramType::DYNAMIC)
         .map(|p: &ElfPhdr| (base + p.p_vaddr()) as *mut ElfDyn)
         .unwrap_or(core::ptr::null_mut());
+    let dynamic_info = unsafe { read_dynamic_info(dynamic_ptr, base) };
 
     let phdrs = dylib.phdrs();
     let phdr = if phdrs.is_empty() {
@@ -98,6 +375,12 @@ pub(crate) fn finalize_raw_dylib(dylib: &mut ElfDylib, file_path: Option<&str>)
 
     let user_data = dylib.user_data_mut().unwrap();
     user_data.needed_libs = needed_libs;
+    user_data.flags_1 = dynamic_info.flags_1;
+    user_data.soname = dynamic_info.soname;
+    user_data.filters = dynamic_info.filters;
+    user_data.auxiliaries = dynamic_info.auxiliaries;
+    user_data.relocations = dynamic_info.relocations;
+    user_data.plt_relocations = dynamic_info.plt_relocations;
     let c_name = CString::new(name).unwrap();
 
     let mut link_map = Box::new(LinkMap {
@@ -117,10 +400,11 @@ pub(crate) fn finalize_raw_dylib(dylib: &mut ElfDylib, file_path: Option<&str>)
     user_data.link_map = Some(link_map);
     user_data.c_name = Some(c_name);
 
-    // Get file identity (inode) if path is provided
+    // Get file identity (inode) and the absolute path if path is provided
     if let Some(path) = file_path {
         if let Ok(identity) = crate::os::get_file_inode(path) {
             user_data.file_identity = Some(identity);
+            user_data.resolved_path = crate::os::absolute_path(path);
             log::debug!(
                 "Stored file identity for [{}]: dev={}, ino={}",
                 path,
@@ -133,20 +417,511 @@ pub(crate) fn finalize_raw_dylib(dylib: &mut ElfDylib, file_path: Option<&str>)
     }
 }
 
+/// Entries of the dynamic table that the loader does not interpret for us.
+#[derive(Default)]
+struct DynamicInfo {
+    flags_1: usize,
+    soname: Option<String>,
+    filters: Vec<String>,
+    auxiliaries: Vec<String>,
+    relocations: usize,
+    plt_relocations: usize,
+}
+
+/// Reads `DT_FLAGS_1`, `DT_SONAME`, `DT_FILTER`, `DT_AUXILIARY` and the relocation table
+/// sizes from a `DT_NULL`-terminated dynamic table.
+unsafe fn read_dynamic_info(mut dynamic: *const ElfDyn, base: usize) -> DynamicInfo {
+    let mut info = DynamicInfo::default();
+    if dynamic.is_null() {
+        return info;
+    }
+    let mut strtab = None;
+    let mut soname = None;
+    let (mut filters, mut auxiliaries) = (Vec::new(), Vec::new());
+    let (mut relasz, mut relaent, mut relsz, mut relent) = (0, 0, 0, 0);
+    let (mut pltrelsz, mut pltrel) = (0, 0);
+    let (mut relr, mut relrsz) = (None, 0);
+    unsafe {
+        while (*dynamic).tag() != ElfDynamicTag::NULL {
+            let tag = (*dynamic).tag();
+            let value = (*dynamic).value();
+            match tag.raw() as i64 {
+                _ if tag == ElfDynamicTag::STRTAB => strtab = Some(value),
+                DT_SONAME => soname = Some(value),
+                DT_FILTER => filters.push(value),
+                DT_AUXILIARY => auxiliaries.push(value),
+                DT_FLAGS_1 => info.flags_1 = value,
+                DT_RELASZ => relasz = value,
+                DT_RELAENT => relaent = value,
+                DT_RELSZ => relsz = value,
+                DT_RELENT => relent = value,
+                DT_PLTRELSZ => pltrelsz = value,
+                DT_PLTREL => pltrel = value as i64,
+                DT_RELR => relr = Some(value),
+                DT_RELRSZ => relrsz = value,
+                _ => {}
+            }
+            dynamic = dynamic.add(1);
+        }
+    }
+    if let Some(strtab) = strtab {
+        let strtab = dynamic_addr(base, strtab);
+        let string = |offset: usize| {
+            let name = unsafe { CStr::from_ptr((strtab + offset) as *const c_char) };
+            name.to_str().ok().map(ToOwned::to_owned)
+        };
+        info.soname = soname.and_then(&string);
+        info.filters = filters.into_iter().filter_map(&string).collect();
+        info.auxiliaries = auxiliaries.into_iter().filter_map(&string).collect();
+    }
+    let count = |size: usize, ent: usize| size.checked_div(ent).unwrap_or(0);
+    info.relocations = count(relasz, relaent) + count(relsz, relent);
+    info.plt_relocations = if pltrel == DT_RELA {
+        count(pltrelsz, size_of::<ElfRela>())
+    } else {
+        count(pltrelsz, size_of::<ElfRel>())
+    };
+    if let Some(relr) = relr {
+        let words = unsafe {
+            core::slice::from_raw_parts(
+                dynamic_addr(base, relr) as *const usize,
+                relrsz / size_of::<usize>(),
+            )
+        };
+        // An even word relocates one address, an odd one is a bitmap of the following words.
+        info.relocations += words
+            .iter()
+            .map(|word| {
+                if word & 1 == 0 {
+                    1
+                } else {
+                    word.count_ones() as usize - 1
+                }
+            })
+            .sum::<usize>();
+    }
+    info
+}
+
+/// Turns an address-valued dynamic entry into an absolute address.
+///
+/// The entries are always link-time relative here. For objects the host linker mapped,
+/// `dynamic_table` finds the copy in which `host_init` made the entries glibc rebased
+/// relative again, and musl and the vDSO tables are never rebased.
+#[inline]
+fn dynamic_addr(base: usize, value: usize) -> usize {
+    base + value
+}
+
+/// Locates the in-memory dynamic table of a loaded library.
+fn dynamic_table(lib: &LoadedDylib) -> *const ElfDyn {
+    lib.phdrs()
+        .unwrap_or(&[])
+        .iter()
+        .find(|p| p.program_type() == ElfProgramType::DYNAMIC)
+        .map(|p| (lib.base() + p.p_vaddr()) as *const ElfDyn)
+        .unwrap_or(null())
+}
+
+/// Finds the descriptor of the note with the given type and name in a `PT_NOTE` segment.
+fn find_note<'a>(mut notes: &'a [u8], align: usize, n_type: u32, name: &[u8]) -> Option<&'a [u8]> {
+    let read_u32 = |bytes: &[u8], at: usize| {
+        Some(u32::from_ne_bytes(bytes.get(at..at + 4)?.try_into().unwrap()) as usize)
+    };
+    while notes.len() >= 12 {
+        let namesz = read_u32(notes, 0)?;
+        let descsz = read_u32(notes, 4)?;
+        let kind = read_u32(notes, 8)?;
+        let desc_start = 12usize
+            .checked_add(namesz)?
+            .checked_next_multiple_of(align)?;
+        let desc_end = desc_start.checked_add(descsz)?;
+        let note_name = notes.get(12..12 + namesz)?;
+        let desc = notes.get(desc_start..desc_end)?;
+        if kind == n_type as usize && note_name == name {
+            return Some(desc);
+        }
+        notes = notes.get(desc_end.checked_next_multiple_of(align)?..)?;
+    }
+    None
+}
+
+/// The symbol versioning tables of a loaded library.
+#[cfg(feature = "version")]
+struct VersionTables {
+    strtab: usize,
+    verdef: Option<usize>,
+    verdefnum: usize,
+    versym: Option<*const u16>,
+}
+
+#[cfg(feature = "version")]
+impl VersionTables {
+    /// Set in a `DT_VERSYM` entry for a non-default version, `foo@V1` rather than `foo@@V2`.
+    const HIDDEN: u16 = 0x8000;
+
+    unsafe fn new(lib: &LoadedDylib) -> Option<Self> {
+        let base = lib.base();
+        let mut dynamic = dynamic_table(lib);
+        if dynamic.is_null() {
+            return None;
+        }
+        let mut tables = VersionTables {
+            strtab: 0,
+            verdef: None,
+            verdefnum: 0,
+            versym: None,
+        };
+        unsafe {
+            while (*dynamic).tag() != ElfDynamicTag::NULL {
+                let tag = (*dynamic).tag();
+                if tag == ElfDynamicTag::STRTAB {
+                    tables.strtab = dynamic_addr(base, (*dynamic).value());
+                } else if tag == ElfDynamicTag::VERDEF {
+                    tables.verdef = Some(dynamic_addr(base, (*dynamic).value()));
+                } else if tag.raw() as i64 == crate::abi::dynamic::DT_VERDEFNUM {
+                    tables.verdefnum = (*dynamic).value();
+                } else if tag.raw() as i64 == crate::abi::dynamic::DT_VERSYM {
+                    tables.versym = Some(dynamic_addr(base, (*dynamic).value()) as *const u16);
+                }
+                dynamic = dynamic.add(1);
+            }
+        }
+        (tables.strtab != 0).then_some(tables)
+    }
+
+    /// The version definitions as `(vd_ndx, name)` pairs, in table order.
+    unsafe fn definitions(&self) -> Vec<(u16, &'static CStr)> {
+        #[allow(dead_code)]
+        #[repr(C)]
+        struct Verdef {
+            vd_version: u16,
+            vd_flags: u16,
+            vd_ndx: u16,
+            vd_cnt: u16,
+            vd_hash: u32,
+            vd_aux: u32,
+            vd_next: u32,
+        }
+        #[repr(C)]
+        struct Verdaux {
+            vda_name: u32,
+            vda_next: u32,
+        }
+
+        let Some(mut cur) = self.verdef else {
+            return Vec::new();
+        };
+        let mut definitions = Vec::with_capacity(self.verdefnum);
+        for _ in 0..self.verdefnum {
+            let def = unsafe { &*(cur as *const Verdef) };
+            if def.vd_cnt != 0 {
+                let aux = unsafe { &*((cur + def.vd_aux as usize) as *const Verdaux) };
+                let name = unsafe {
+                    CStr::from_ptr((self.strtab + aux.vda_name as usize) as *const c_char)
+                };
+                definitions.push((def.vd_ndx, name));
+            }
+            if def.vd_next == 0 {
+                break;
+            }
+            cur += def.vd_next as usize;
+        }
+        definitions
+    }
+}
+
+/// Reads the names of all version definitions (`DT_VERDEF`) of a loaded library.
+#[cfg(feature = "version")]
+unsafe fn read_version_definitions(lib: &LoadedDylib) -> Vec<String> {
+    let Some(tables) = (unsafe { VersionTables::new(lib) }) else {
+        return Vec::new();
+    };
+    let mut versions = Vec::new();
+    for (_, name) in unsafe { tables.definitions() } {
+        if let Ok(name) = name.to_str()
+            && !versions.iter().any(|v: &String| v == name)
+        {
+            versions.push(name.to_owned());
+        }
+    }
+    versions
+}
+
+/// Binds an unversioned request the way glibc does when `lib` defines `name` in several
+/// versions: to the default version (`foo@@V2`), never to a hidden one (`foo@V1`).
+///
+/// `sym` is what the symbol table lookup found; it is kept unless a hidden definition of
+/// `name` exists. Returns `None` if every definition of `name` is hidden.
+#[cfg(feature = "version")]
+unsafe fn prefer_default_version<'lib, T>(
+    lib: &'lib LoadedDylib,
+    name: &str,
+    sym: Symbol<'lib, T>,
+) -> Option<Symbol<'lib, T>> {
+    let defaults = lib
+        .user_data()
+        .default_versions
+        .call_once(|| unsafe { default_versions(lib) });
+    let Some(default) = defaults.get(name) else {
+        return Some(sym);
+    };
+    match (*default)? {
+        // VER_NDX_LOCAL and VER_NDX_GLOBAL: an unversioned definition.
+        0 | 1 => Some(sym),
+        ndx => {
+            let tables = unsafe { VersionTables::new(lib) }?;
+            let definitions = unsafe { tables.definitions() };
+            let (_, version) = definitions.iter().find(|(def, _)| *def == ndx)?;
+            unsafe { lib.get_version::<T>(name, version.to_str().ok()?) }
+        }
+    }
+}
+
+/// Scans `.dynsym` once for the names `lib` defines in a hidden version, mapping each to
+/// the `DT_VERSYM` index of its default definition, if it has one.
+#[cfg(feature = "version")]
+unsafe fn default_versions(lib: &LoadedDylib) -> BTreeMap<String, Option<u16>> {
+    let mut defaults = BTreeMap::new();
+    let Some(versym) = (unsafe { VersionTables::new(lib) }).and_then(|tables| tables.versym) else {
+        return defaults;
+    };
+    let symtab = lib.symtab();
+    let mut visible = BTreeMap::new();
+    for i in 0..symtab.count_syms() {
+        let (entry, syminfo) = symtab.symbol_idx(i);
+        if entry.st_value() == 0 {
+            continue;
+        }
+        let Some(name) = syminfo.cname().and_then(|cname| cname.to_str().ok()) else {
+            continue;
+        };
+        let ndx = unsafe { *versym.add(i) };
+        if ndx & VersionTables::HIDDEN != 0 {
+            defaults.entry(name.to_owned()).or_insert(None);
+        } else {
+            visible.insert(name, ndx);
+        }
+    }
+    for (name, default) in defaults.iter_mut() {
+        *default = visible.get(name.as_str()).copied();
+    }
+    defaults
+}
+
+/// The calling thread's address of the `STT_TLS` symbol `name` defined by `lib`.
+fn thread_local_addr(lib: &LoadedDylib, name: &str) -> Option<*mut u8> {
+    let modid = lib.tls_mod_id()?;
+    let info = defined_symbol_info(lib, name).filter(|info| info.kind == SymbolType::Tls)?;
+    // Laid out like `tls_index`: the module id, then the offset in its block.
+    let index = [modid.get(), info.value];
+    Some(<ActiveTlsResolver as TlsResolver>::tls_get_addr(
+        index.as_ptr().cast(),
+    ))
+}
+
+/// Looks up the symbol table entry of a symbol defined by `lib`.
+fn defined_symbol_info<'lib>(lib: &'lib LoadedDylib, name: &str) -> Option<SymbolInfo<'lib>> {
+    let symtab = lib.symtab();
+    (0..symtab.count_syms()).find_map(|i| {
+        let (sym, syminfo) = symtab.symbol_idx(i);
+        if sym.st_value() == 0 || !sym.is_ok_bind() {
+            return None;
+        }
+        syminfo
+            .cname()
+            .is_some_and(|cname| cname.to_bytes() == name.as_bytes())
+            .then(|| SymbolInfo {
+                library: lib.name(),
+                address: lib.base() + sym.st_value(),
+                value: sym.st_value(),
+                size: sym.st_size(),
+                binding: SymbolBinding::from_raw(sym.st_bind()),
+                kind: SymbolType::from_raw(sym.st_type()),
+                visibility: SymbolVisibility::from_raw(sym.st_other()),
+            })
+    })
+}
+
+/// The `st_size` of the definition of `name` in `lib` that lies at `address`.
+///
+/// Matching the address picks the right entry among several versions of a name.
+fn defined_symbol_size(lib: &LoadedDylib, name: &str, address: usize) -> Option<usize> {
+    let symtab = lib.symtab();
+    (0..symtab.count_syms()).find_map(|i| {
+        let (sym, syminfo) = symtab.symbol_idx(i);
+        (sym.st_value() != 0
+            && lib.base() + sym.st_value() == address
+            && syminfo
+                .cname()
+                .is_some_and(|cname| cname.to_bytes() == name.as_bytes()))
+        .then(|| sym.st_size())
+    })
+}
+
+/// The binding of a symbol (`STB_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolBinding {
+    Local,
+    Global,
+    Weak,
+    /// An OS- or processor-specific binding, e.g. `STB_GNU_UNIQUE`.
+    Other(u8),
+}
+
+impl SymbolBinding {
+    fn from_raw(bind: u8) -> Self {
+        match bind {
+            0 => Self::Local,
+            1 => Self::Global,
+            2 => Self::Weak,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// The type of a symbol (`STT_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolType {
+    NoType,
+    Object,
+    Func,
+    Section,
+    File,
+    Common,
+    Tls,
+    /// An OS- or processor-specific type, e.g. `STT_GNU_IFUNC`.
+    Other(u8),
+}
+
+impl SymbolType {
+    fn from_raw(kind: u8) -> Self {
+        match kind {
+            0 => Self::NoType,
+            1 => Self::Object,
+            2 => Self::Func,
+            3 => Self::Section,
+            4 => Self::File,
+            5 => Self::Common,
+            6 => Self::Tls,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// The visibility of a symbol (`STV_*`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolVisibility {
+    Default,
+    Internal,
+    Hidden,
+    Protected,
+}
+
+impl SymbolVisibility {
+    fn from_raw(other: u8) -> Self {
+        match other & 0x3 {
+            0 => Self::Default,
+            1 => Self::Internal,
+            2 => Self::Hidden,
+            _ => Self::Protected,
+        }
+    }
+}
+
+/// Symbol table metadata of a symbol, as returned by [`ElfLibrary::symbol_info`].
+#[derive(Debug, Clone, Copy)]
+pub struct SymbolInfo<'lib> {
+    /// The name of the library that defines the symbol.
+    pub library: &'lib str,
+    /// The address of the symbol in memory.
+    pub address: usize,
+    /// The raw `st_value`, relative to the library's base address.
+    pub value: usize,
+    /// The raw `st_size`. Zero if the size is unknown.
+    pub size: usize,
+    pub binding: SymbolBinding,
+    pub kind: SymbolType,
+    pub visibility: SymbolVisibility,
+}
+
+bitflags::bitflags! {
+    /// The page protection of a segment, as returned by [`ElfLibrary::segment_protections`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Protection: u32 {
+        const READ = PF_R;
+        const WRITE = PF_W;
+        const EXEC = PF_X;
+    }
+}
+
+/// How much relocation work loading a library took, as returned by [`ElfLibrary::load_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadStats {
+    /// The number of entries in the dynamic symbol table.
+    pub symbols: usize,
+    /// The number of relocations applied while loading.
+    pub relocations: usize,
+    /// The number of PLT relocations left to lazy binding.
+    pub deferred: usize,
+}
+
 /// Represents a successfully loaded and relocated dynamic library.
 ///
 /// This is the primary interface for interacting with a loaded library,
 /// providing methods to look up symbols and inspect metadata.
-#[derive(Clone)]
 pub struct ElfLibrary {
     pub(crate) inner: LoadedDylib,
     /// The flattened dependency scope (Searchlist) used by this library.
     pub(crate) deps: Option<Arc<[LoadedDylib]>>,
+    /// Addresses resolved by [`ElfLibrary::get_cached`], shared by clones of this handle.
+    pub(crate) symbol_cache: Arc<Mutex<SymbolCache>>,
+}
+
+impl ElfLibrary {
+    /// Creates a handle, counting it in the library's handle count.
+    pub(crate) fn new_handle(inner: LoadedDylib, deps: Arc<[LoadedDylib]>) -> Self {
+        inner.user_data().handles.fetch_add(1, Ordering::Relaxed);
+        Self {
+            inner,
+            deps: Some(deps),
+            symbol_cache: Arc::default(),
+        }
+    }
+}
+
+impl Clone for ElfLibrary {
+    fn clone(&self) -> Self {
+        self.inner
+            .user_data()
+            .handles
+            .fetch_add(1, Ordering::Relaxed);
+        Self {
+            inner: self.inner.clone(),
+            deps: self.deps.clone(),
+            symbol_cache: self.symbol_cache.clone(),
+        }
+    }
 }
 
 impl Debug for ElfLibrary {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.debug_struct("Dylib").field("inner", &self.inner).finish()
+        // The registry flags are left out: reading them takes the registry lock, which the
+        // caller may hold.
+        let deps = self
+            .deps
+            .as_deref()
+            .unwrap_or(&[])
+            .iter()
+            .map(|dep| dep.shortname())
+            .collect::<Vec<_>>();
+        f.debug_struct("Dylib")
+            .field("name", &self.name())
+            .field("base", &format_args!("{:#x}", self.base()))
+            .field("mapped_len", &format_args!("{:#x}", self.mapped_len()))
+            .field("needed_libs", &self.needed_libs())
+            .field("deps", &deps)
+            .finish()
     }
 }
 
@@ -202,6 +977,34 @@ impl ElfLibrary {
         self.inner.shortname()
     }
 
+    /// Get the absolute path of the file the library was loaded from.
+    ///
+    /// This is where the search for a bare name such as `libfoo.so` ended up, e.g. to tell
+    /// which of several installed copies was opened. Symbolic links are not resolved.
+    /// Returns `None` for libraries that were not loaded from a file, such as those loaded
+    /// from memory or mapped by the host dynamic linker.
+    #[inline]
+    pub fn resolved_path(&self) -> Option<&str> {
+        self.inner.user_data().resolved_path.as_deref()
+    }
+
+    /// Whether the library was mapped by the host dynamic linker, like the libc of the process.
+    ///
+    /// Such libraries were only registered with this crate when it started, so closing
+    /// a handle to them never unmaps them.
+    #[inline]
+    pub fn is_host_library(&self) -> bool {
+        self.inner.user_data().host
+    }
+
+    /// Get the `DT_SONAME` of the dynamic library, if it declares one.
+    ///
+    /// This can differ from [`ElfLibrary::shortname`], which is derived from the file name.
+    #[inline]
+    pub fn soname(&self) -> Option<&str> {
+        self.inner.user_data().soname.as_deref()
+    }
+
     /// Get the current flags of the dynamic library from the global registry.
     pub fn flags(&self) -> OpenFlags {
         use super::register::MANAGER;
@@ -228,17 +1031,253 @@ impl ElfLibrary {
         self.inner.phdrs()
     }
 
+    /// Get the first program header of the given type.
+    #[inline]
+    pub fn phdr(&self, p_type: ElfProgramType) -> Option<&ElfPhdr> {
+        self.phdrs()?.iter().find(|p| p.program_type() == p_type)
+    }
+
+    /// Get the `PT_TLS` segment of the dynamic library, if it has thread-local storage.
+    #[inline]
+    pub fn tls_segment(&self) -> Option<&ElfPhdr> {
+        self.phdr(ElfProgramType::TLS)
+    }
+
+    /// Get the TLS initialization image (`.tdata`) of the dynamic library.
+    ///
+    /// This is the template each thread's TLS block is initialized from, not the block
+    /// of any particular thread. The zero-initialized `.tbss` part is not included.
+    pub fn tls_init_image(&self) -> Option<&[u8]> {
+        let tls = self.tls_segment()?;
+        let image = (self.base() + tls.p_vaddr()) as *const u8;
+        // SAFETY: the segment is part of the mapping, which lives as long as `self`.
+        Some(unsafe { core::slice::from_raw_parts(image, tls.p_filesz()) })
+    }
+
+    /// Get the GNU build-id of the dynamic library, as recorded in `.note.gnu.build-id`.
+    ///
+    /// This is what debug symbol servers index split debug info by. Returns `None` if no
+    /// `PT_NOTE` segment holds an `NT_GNU_BUILD_ID` note, e.g. when linked with
+    /// `--build-id=none`.
+    pub fn build_id(&self) -> Option<&[u8]> {
+        self.phdrs()?
+            .iter()
+            .filter(|phdr| phdr.program_type() == ElfProgramType::NOTE)
+            .find_map(|phdr| {
+                let start = (self.base() + phdr.p_vaddr()) as *const u8;
+                // SAFETY: the segment is part of the mapping, which lives as long as `self`.
+                let notes = unsafe { core::slice::from_raw_parts(start, phdr.p_filesz()) };
+                find_note(notes, phdr.p_align().max(4), NT_GNU_BUILD_ID, b"GNU\0")
+            })
+    }
+
+    /// Get the address range and protection of every `PT_LOAD` segment.
+    ///
+    /// The protection comes from each segment's `p_flags`. The `PT_GNU_RELRO` range is made
+    /// read-only once relocation is done, so it is reported without [`Protection::WRITE`],
+    /// split off from the segment it belongs to.
+    pub fn segment_protections(&self) -> Vec<(Range<usize>, Protection)> {
+        let Some(phdrs) = self.phdrs() else {
+            return Vec::new();
+        };
+        let base = self.base();
+        let relro = self
+            .phdr(ElfProgramType::GNU_RELRO)
+            .map(|p| base + p.p_vaddr()..base + p.p_vaddr() + p.p_memsz());
+        let mut segments = Vec::new();
+        for phdr in phdrs
+            .iter()
+            .filter(|p| p.program_type() == ElfProgramType::LOAD)
+        {
+            let range = base + phdr.p_vaddr()..base + phdr.p_vaddr() + phdr.p_memsz();
+            let prot = Protection::from_bits_truncate(phdr.p_flags());
+            let Some(relro) = relro
+                .clone()
+                .filter(|relro| relro.start < range.end && range.start < relro.end)
+            else {
+                segments.push((range, prot));
+                continue;
+            };
+            let (start, end) = (relro.start.max(range.start), relro.end.min(range.end));
+            if range.start < start {
+                segments.push((range.start..start, prot));
+            }
+            segments.push((start..end, prot - Protection::WRITE));
+            if end < range.end {
+                segments.push((end..range.end, prot));
+            }
+        }
+        segments
+    }
+
+    /// Whether any segment is both writable and executable, see
+    /// [`ElfLibrary::segment_protections`].
+    pub fn has_writable_executable_segment(&self) -> bool {
+        self.segment_protections()
+            .iter()
+            .any(|(_, prot)| prot.contains(Protection::WRITE | Protection::EXEC))
+    }
+
+    /// Set the `argc`, `argv` and `envp` passed to the constructors of loaded libraries.
+    ///
+    /// They are otherwise taken from the host's libc at startup, which is impossible without
+    /// `std` or when its symbols are not found. `envp` is also where this crate reads
+    /// `LD_LIBRARY_PATH` and its other variables, each the first time it is needed.
+    ///
+    /// # Safety
+    /// The arrays must be null-terminated, stay valid for the rest of the process, and this
+    /// must not be called while another thread loads a library.
+    pub unsafe fn set_init_args(argc: usize, argv: *const *mut c_char, envp: *const *const c_char) {
+        unsafe {
+            *core::ptr::addr_of_mut!(ARGC) = argc;
+            *core::ptr::addr_of_mut!(ARGV) = argv;
+            *core::ptr::addr_of_mut!(ENVP) = envp;
+        }
+    }
+
+    /// Set how long a constructor may run before the init watchdog reports it.
+    ///
+    /// The report is a warning naming the library, with the backtrace of the call that ran
+    /// the constructor. The constructor itself is left running. The default is 5 seconds.
+    #[cfg(feature = "init-watchdog")]
+    pub fn set_init_watchdog(threshold: core::time::Duration) {
+        crate::utils::watchdog::set_threshold(threshold);
+    }
+
+    /// Run the constructors deferred by [`ElfLibrary::dlopen_no_init`].
+    ///
+    /// Constructors of the dependencies in the library's searchlist run first, deepest first.
+    /// Each library's constructors run at most once; libraries whose constructors already ran
+    /// are skipped.
+    pub fn run_constructors(&self) {
+        let searchlist = self
+            .deps
+            .as_deref()
+            .unwrap_or(core::slice::from_ref(&self.inner));
+        for lib in searchlist.iter().rev() {
+            let data = lib.user_data();
+            if data.init_deferred.swap(false, Ordering::AcqRel) {
+                unsafe { run_deferred_init(lib) };
+            }
+        }
+    }
+
+    /// Run the library's destructors now, without unloading it.
+    ///
+    /// Functions registered with `__cxa_atexit` by the library run first, then `DT_FINI_ARRAY`
+    /// in reverse and `DT_FINI`, the order of an unload. They run at most once: later calls
+    /// and the eventual unload skip them. Dependencies are left alone, since other libraries
+    /// may still use them. The library must not be used afterwards in ways that rely on the
+    /// state its destructors tore down. Libraries mapped by the host dynamic linker are left
+    /// to it.
+    pub fn run_destructors(&self) {
+        let data = self.inner.user_data();
+        // Constructors that have not run yet set up nothing to tear down.
+        if data.host
+            || data.init_deferred.load(Ordering::Acquire)
+            || data.fini_done.load(Ordering::Acquire)
+        {
+            return;
+        }
+        let base = self.base();
+        super::register::finalize(base as *mut _, Some(base..base + self.mapped_len()));
+        unsafe { run_fini(&self.inner) };
+    }
+
+    /// Get the load bias: the difference between runtime and link-time addresses.
+    ///
+    /// Subtracting it from a runtime address gives the address used by the symbol and debug
+    /// information of the file. It is the same as [`ElfLibrary::base`]; for a library whose
+    /// first `PT_LOAD` has a nonzero `p_vaddr`, the mapping itself starts that far above it.
+    #[inline]
+    pub fn load_bias(&self) -> usize {
+        self.base()
+    }
+
+    /// Whether the library asks for an executable stack.
+    ///
+    /// This is the `PF_X` bit of `PT_GNU_STACK`. A library without that header is treated as
+    /// requiring an executable stack, like the kernel and glibc do.
+    pub fn executable_stack(&self) -> bool {
+        self.phdr(ElfProgramType::GNU_STACK)
+            .is_none_or(|p| p.p_flags() & PF_X != 0)
+    }
+
+    /// Get the number of entries in the dynamic symbol table, including the null symbol.
+    #[inline]
+    pub fn symbol_count(&self) -> usize {
+        self.inner.symtab().count_syms()
+    }
+
+    /// Get the names of the symbols the library imports.
+    ///
+    /// These are the undefined entries of the dynamic symbol table with global binding. Weak
+    /// references are left out, since the library loads without them. The list does not
+    /// depend on relocation, so it can vet what a library needs from its scope.
+    pub fn undefined_symbols(&self) -> Vec<String> {
+        strong_undefined_symbols(&self.inner)
+    }
+
+    /// Get how much relocation work loading the library took.
+    ///
+    /// The counts come from the relocation tables of the library itself. With lazy binding
+    /// the PLT relocations are reported as deferred, even once some of them were bound.
+    pub fn load_stats(&self) -> LoadStats {
+        let extra_data = self.inner.user_data();
+        let (relocations, deferred) = if extra_data.lazy_binding.load(Ordering::Relaxed) {
+            (extra_data.relocations, extra_data.plt_relocations)
+        } else {
+            (extra_data.relocations + extra_data.plt_relocations, 0)
+        };
+        LoadStats {
+            symbols: self.symbol_count(),
+            relocations,
+            deferred,
+        }
+    }
+
     /// Get the needed libs' name of the elf object.
     #[inline]
     pub fn needed_libs(&self) -> &[String] {
         self.inner.needed_libs()
     }
 
+    /// Get where each direct dependency of the library was found.
+    ///
+    /// Returns `(needed, path)` pairs, where `needed` is the `DT_NEEDED` entry and `path` is
+    /// the name the dependency was loaded under, i.e. the path it was resolved to on disk.
+    /// Dependencies that are not part of this library's searchlist are omitted.
+    pub fn resolved_dependencies(&self) -> Vec<(&str, &str)> {
+        use super::register::MANAGER;
+        let deps = self.deps.as_deref().unwrap_or(&[]);
+        let manager = crate::lock_read!(MANAGER);
+        self.needed_libs()
+            .iter()
+            .filter_map(|needed| {
+                let lookup = manager.lookup(needed)?;
+                let dep = deps
+                    .iter()
+                    .find(|dep| dep.shortname() == lookup.shortname())?;
+                Some((needed.as_str(), dep.name()))
+            })
+            .collect()
+    }
+
     /// Get a pointer to a function or static variable by symbol name.
     ///
     /// The symbol is interpreted as-is; no mangling is done. This means that symbols like `x::y` are
     /// most likely invalid.
     ///
+    /// Only definitions match: a library on the searchlist that merely references `name`
+    /// through an undefined symbol table entry is skipped.
+    ///
+    /// With the `version` feature, a library that defines `name` in several versions
+    /// provides its default one (`name@@V2`), never a hidden one (`name@V1`), as in glibc.
+    /// Hidden versions are reached through [`ElfLibrary::get_version`].
+    ///
+    /// Thread-local variables are reached through [`ElfLibrary::get_thread_local`]; `get`
+    /// does not give a usable pointer for them.
+    ///
     /// # Safety
     /// Users of this API must specify the correct type of the function or variable loaded.
     ///
@@ -266,6 +1305,289 @@ impl ElfLibrary {
         find_symbol(self.deps.as_ref().unwrap(), name)
     }
 
+    /// Get a symbol from the library's searchlist, or else from the global scope.
+    ///
+    /// This is [`ElfLibrary::get`] followed by [`dlsym_default`](crate::dlsym_default), for
+    /// symbols that the library's own dependency tree does not provide but an `RTLD_GLOBAL`
+    /// library does.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    /// A symbol from the global scope belongs to another library, which must not be
+    /// unloaded while the symbol is in use.
+    pub unsafe fn get_or_global<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, T>> {
+        unsafe { self.get(name) }.or_else(|err| unsafe { global_find(name) }.ok_or(err))
+    }
+
+    /// Get a function or static variable by symbol name, caching the resolved address.
+    ///
+    /// This is meant for hot paths that look up the same symbols over and over. The first
+    /// lookup of a name walks the searchlist like [`ElfLibrary::get`]; later lookups through
+    /// this handle or its clones are a hash map probe. The cache is dropped whenever a
+    /// library is loaded or unloaded, and it lives as long as the handle, so it never
+    /// outlives the library.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary ,OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// for _ in 0..10_000 {
+    ///     let callback = unsafe { lib.get_cached::<extern "C" fn()>("callback").unwrap() };
+    ///     callback();
+    /// }
+    /// ```
+    pub unsafe fn get_cached<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, T>> {
+        const {
+            assert!(
+                core::mem::size_of::<Symbol<'static, ()>>() == core::mem::size_of::<usize>(),
+                "a symbol must be a bare address"
+            )
+        };
+        let generation = super::register::generation();
+        let mut cache = self.symbol_cache.lock();
+        let addr = match cache.get(name, generation) {
+            Some(addr) => addr,
+            None => {
+                let sym = unsafe { self.get::<()>(name)? };
+                let addr = sym.into_raw() as usize;
+                cache.insert(name, addr);
+                addr
+            }
+        };
+        // SAFETY: a `Symbol` is the address of the definition, checked above to be its size.
+        Ok(unsafe { core::mem::transmute_copy(&addr) })
+    }
+
+    /// Get the address of a symbol whose name is given as a C string.
+    ///
+    /// ELF symbol names are raw bytes, so unlike [`ElfLibrary::get`] this also finds names
+    /// that are not valid UTF-8. Those are matched by walking the symbol tables of the
+    /// searchlist, without filter libraries or symbol version preferences, and never
+    /// resolve to `STT_TLS` or `STT_GNU_IFUNC` symbols.
+    pub fn get_cstr(&self, name: &CStr) -> Result<SymbolAddr> {
+        let libs = self.deps.as_deref().unwrap();
+        let addr = match name.to_str() {
+            Ok(name) => find_symbol::<()>(libs, name).ok().map(|sym| sym.into_raw()),
+            Err(_) => find_symbol_bytes(libs, name.to_bytes()),
+        };
+        addr.map(SymbolAddr::from_raw)
+            .ok_or_else(|| find_symbol_error(format!("can not find symbol:{:?}", name)))
+    }
+
+    /// Get a pointer to a function or static variable defined by this library itself.
+    ///
+    /// Unlike [`ElfLibrary::get`], the dependency searchlist is not consulted, so a
+    /// same-named export from a dependency is never returned. This mirrors the intent
+    /// of `RTLD_FIRST` on macOS.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{Symbol, ElfLibrary ,OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// let own: Symbol<unsafe extern fn() -> i32> =
+    ///     unsafe { lib.get_local("shared_name").unwrap() };
+    /// ```
+    #[inline]
+    pub unsafe fn get_local<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, T>> {
+        find_symbol(core::slice::from_ref(&self.inner), name)
+    }
+
+    /// Get the calling thread's instance of a thread-local variable.
+    ///
+    /// The value of an `STT_TLS` symbol is an offset into the thread-local block of its
+    /// library, so [`ElfLibrary::get`] cannot return a usable pointer for it. This walks the
+    /// searchlist like [`ElfLibrary::get`] and returns the address of the variable in the
+    /// calling thread's block, allocating the block if the thread has none yet. The pointer
+    /// is only valid on the calling thread, until it exits.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the variable.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary ,OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// let counter = unsafe { lib.get_thread_local::<i32>("thread_counter").unwrap() };
+    /// unsafe { *counter += 1 };
+    /// ```
+    pub unsafe fn get_thread_local<T>(&self, name: &str) -> Result<*mut T> {
+        let libs = self.deps.as_deref().unwrap();
+        let lib = libs
+            .iter()
+            .find(|lib| unsafe { filtered_get::<()>(lib, name) }.is_some())
+            .ok_or_else(|| find_symbol_error(format!("can not find symbol:{}", name)))?;
+        let addr = thread_local_addr(lib, name).ok_or_else(|| {
+            find_symbol_error(format!(
+                "symbol [{}] of [{}] is not thread-local",
+                name,
+                lib.name()
+            ))
+        })?;
+        Ok(addr.cast())
+    }
+
+    /// Get the symbol table metadata of a symbol.
+    ///
+    /// The searchlist is walked like [`ElfLibrary::get`], and the entry of the first library
+    /// defining `name` is returned, together with that library's name.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary ,OpenFlags, SymbolType};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// let info = lib.symbol_info("awesome_function").unwrap();
+    /// assert_eq!(info.kind, SymbolType::Func);
+    /// ```
+    pub fn symbol_info(&self, name: &str) -> Option<SymbolInfo<'_>> {
+        self.deps
+            .as_deref()
+            .unwrap()
+            .iter()
+            .find_map(|lib| defined_symbol_info(lib, name))
+    }
+
+    /// Get every definition of a symbol in the library's searchlist.
+    ///
+    /// Where [`ElfLibrary::get`] stops at the first match, this returns one
+    /// `(library name, symbol)` pair for each library of the searchlist that answers for
+    /// `name`, in searchlist order. Each library is asked the way `get` asks it: a filter
+    /// library answers with its filtee's definition, and a name with several versions
+    /// resolves to the default one. More than one entry means a duplicate definition, and
+    /// the first one is the copy `get` would return.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary ,OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// for (owner, _) in unsafe { lib.get_all::<fn()>("duplicated") } {
+    ///     println!("defined in {}", owner);
+    /// }
+    /// ```
+    pub unsafe fn get_all<'lib, T>(&'lib self, name: &str) -> Vec<(&'lib str, Symbol<'lib, T>)> {
+        self.deps
+            .as_deref()
+            .unwrap()
+            .iter()
+            .filter_map(|lib| {
+                let sym = unsafe { filtered_get::<T>(lib, name) }?;
+                Some((lib.name(), sym))
+            })
+            .collect()
+    }
+
+    /// Get a pointer to a static variable, checking it is large enough to hold a `T`.
+    ///
+    /// This is an opt-in variant of [`ElfLibrary::get`] for data symbols. When the ELF symbol
+    /// records a nonzero size (`st_size`) smaller than `size_of::<T>()`, an error is returned
+    /// instead of a pointer that would read past the end of the object. The symbol is found
+    /// like `get` finds it, and the size is that of the definition returned.
+    ///
+    /// The check has limits: symbols without a recorded size are not checked, a `T` smaller
+    /// than the object is accepted, and function signatures cannot be validated at all
+    /// (for functions `st_size` is the length of the code).
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{Symbol, ElfLibrary ,OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// unsafe {
+    ///     let awesome_variable: Symbol<*mut f64> = lib.get_checked("awesome_variable").unwrap();
+    ///     **awesome_variable = 42.0;
+    /// };
+    /// ```
+    pub unsafe fn get_checked<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, *mut T>> {
+        let Some((owner, sym)) = self
+            .deps
+            .as_deref()
+            .unwrap()
+            .iter()
+            .find_map(|lib| unsafe { filtered_lookup::<*mut T>(lib, name) })
+        else {
+            return Err(find_symbol_error(format!("can not find symbol:{}", name)));
+        };
+        let wanted = core::mem::size_of::<T>();
+        if let Some(size) = defined_symbol_size(owner, name, *sym as usize)
+            && size != 0
+            && wanted > size
+        {
+            return Err(find_symbol_error(format!(
+                "symbol [{}] in [{}] is {} bytes, smaller than the requested {} bytes",
+                name,
+                owner.name(),
+                size,
+                wanted
+            )));
+        }
+        Ok(sym)
+    }
+
+    /// Get a shared reference to a static variable of the library.
+    ///
+    /// This saves going through the pointer returned by [`ElfLibrary::get`] by hand. The
+    /// size is checked like [`ElfLibrary::get_checked`] does, and a misaligned address is
+    /// rejected.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the variable loaded, and the
+    /// variable must not be written while the reference is alive, whether by the library
+    /// itself or through [`ElfLibrary::get_static_mut`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// let value: &f64 = unsafe { lib.get_static("awesome_variable").unwrap() };
+    /// ```
+    pub unsafe fn get_static<'lib, T>(&'lib self, name: &str) -> Result<&'lib T> {
+        let ptr = unsafe { self.static_ptr::<T>(name)? };
+        Ok(unsafe { &*ptr })
+    }
+
+    /// Get a mutable reference to a static variable of the library.
+    ///
+    /// Checked like [`ElfLibrary::get_static`].
+    ///
+    /// # Safety
+    /// Besides the correct type, the reference must be the only access to the variable
+    /// while it is alive. Nothing prevents a second call, or another handle to the same
+    /// library, from returning an aliasing reference; neither may the library's own code
+    /// touch the variable meanwhile.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// # let lib = ElfLibrary::dlopen("awesome.so", OpenFlags::RTLD_NOW).unwrap();
+    /// unsafe { *lib.get_static_mut::<f64>("awesome_variable").unwrap() = 42.0 };
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_static_mut<'lib, T>(&'lib self, name: &str) -> Result<&'lib mut T> {
+        let ptr = unsafe { self.static_ptr::<T>(name)? };
+        Ok(unsafe { &mut *ptr })
+    }
+
+    unsafe fn static_ptr<T>(&self, name: &str) -> Result<*mut T> {
+        let ptr = *unsafe { self.get_checked::<T>(name)? };
+        if !ptr.is_aligned() {
+            return Err(find_symbol_error(format!(
+                "symbol [{}] at {:p} is not aligned for the requested type",
+                name, ptr
+            )));
+        }
+        Ok(ptr)
+    }
+
     /// Load a versioned symbol from the dynamic library.
     ///
     /// # Examples
@@ -287,4 +1609,13 @@ impl ElfLibrary {
                 .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
         }
     }
+
+    /// Get the names of the symbol versions defined by the dynamic library.
+    ///
+    /// The list comes from `DT_VERDEF` and includes the base version, which carries the
+    /// library's own name. It is empty if the library defines no versions.
+    #[cfg(feature = "version")]
+    pub fn symbol_versions(&self) -> Vec<String> {
+        unsafe { read_version_definitions(&self.inner) }
+    }
 }
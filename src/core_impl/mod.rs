@@ -3,14 +3,21 @@ mod register;
 mod traits;
 mod types;
 
-pub use loader::ElfLibrary;
-pub use traits::AsFilename;
+pub use loader::{
+    ElfLibrary, LoadStats, Protection, SymbolBinding, SymbolInfo, SymbolType, SymbolVisibility,
+};
+pub use register::{LeakedDylib, UnloadOutcome};
+pub use traits::{AsFilename, Builder};
 
-pub(crate) use loader::{DylibExt, LoadedDylib, find_symbol, new_loader};
 #[cfg(not(feature = "std"))]
-pub(crate) use loader::{ElfDylib, RuntimeLoader, shortname_from_name};
+pub(crate) use loader::RuntimeLoader;
+pub(crate) use loader::{
+    ActiveTlsResolver, DylibExt, ElfDylib, LoadedDylib, filtered_get, filtered_scope_find,
+    find_symbol, find_symbol_bytes, new_dlopen_loader, new_loader, protected_definitions,
+    shortname_from_name, strong_undefined_symbols,
+};
 pub(crate) use register::{
-    GlobalMeta, LibraryLookup, MANAGER, Manager, addr2dso, global_find, next_find, register_loaded,
-    reserve_pending,
+    GlobalMeta, LibraryLookup, MANAGER, Manager, addr2dso, global_find, global_find_bytes,
+    next_find, next_find_bytes, register_loaded, reserve_pending,
 };
 pub(crate) use types::{ARGC, ARGV, ENVP, ExtraData, FileIdentity, LinkMap};
@@ -1,18 +1,25 @@
 use super::{
-    loader::{DylibExt, LoadedDylib},
+    loader::{DylibExt, LoadedDylib, run_fini},
     types::{ExtraData, FileIdentity},
 };
-use crate::{ElfLibrary, OpenFlags};
+use crate::{
+    ElfLibrary, OpenFlags,
+    utils::{ld_debug::ld_debug, trace_resolution},
+};
 use alloc::{
     borrow::{Cow, ToOwned},
     boxed::Box,
     collections::btree_set::BTreeSet,
+    format,
     string::String,
     sync::Arc,
     vec,
     vec::Vec,
 };
-use core::ffi::{c_int, c_void};
+use core::{
+    ffi::{c_int, c_void},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use elf_loader::linker::{KeyId, LinkContext};
 use hashbrown::{DefaultHashBuilder, HashMap};
 use spin::{Lazy, RwLock};
@@ -29,71 +36,223 @@ macro_rules! lock_read {
     ($lock:expr) => {{ $lock.read() }};
 }
 
-impl Drop for ElfLibrary {
-    fn drop(&mut self) {
+/// The result of releasing an [`ElfLibrary`] handle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub enum UnloadOutcome {
+    /// The library was removed from the registry and will be unmapped.
+    Unloaded {
+        /// Whether functions registered with `__cxa_atexit` by the released libraries ran.
+        ///
+        /// This is false when there were none, e.g. because
+        /// [`ElfLibrary::run_destructors`] already ran them. `DT_FINI_ARRAY` and `DT_FINI`
+        /// are not counted; they run when the libraries are dropped.
+        ran_atexit_handlers: bool,
+    },
+    /// Other handles or dependents still keep the library alive.
+    StillReferenced,
+    /// The library is `RTLD_NODELETE` and is never unloaded.
+    NoDelete,
+}
+
+/// A library that stays loaded for the rest of the process, created by [`ElfLibrary::leak`].
+#[derive(Debug, Clone, Copy)]
+pub struct LeakedDylib {
+    lib: &'static ElfLibrary,
+}
+
+impl LeakedDylib {
+    /// Get the leaked handle.
+    #[inline]
+    pub fn library(self) -> &'static ElfLibrary {
+        self.lib
+    }
+
+    /// Get the base address of the library, which never changes.
+    #[inline]
+    pub fn base(self) -> usize {
+        self.lib.base()
+    }
+
+    /// Get a symbol like [`ElfLibrary::get`], valid for the rest of the process.
+    ///
+    /// # Safety
+    /// The same as for [`ElfLibrary::get`].
+    #[inline]
+    pub unsafe fn get<T>(self, name: &str) -> crate::Result<crate::Symbol<'static, T>> {
+        unsafe { self.lib.get(name) }
+    }
+}
+
+impl ElfLibrary {
+    /// Close the handle and unload the library if it was the last reference.
+    ///
+    /// This performs the same work as dropping the handle, but reports whether the
+    /// library was actually unloaded and whether its `__cxa_atexit` handlers ran.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags, UnloadOutcome};
+    /// let lib = ElfLibrary::dlopen("/path/to/plugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// assert!(matches!(lib.close(), UnloadOutcome::Unloaded { .. }));
+    /// ```
+    pub fn close(self) -> UnloadOutcome {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        let outcome = this.do_unload();
+        // SAFETY: each field is moved out exactly once and `this` is not used afterwards.
+        unsafe {
+            drop(core::ptr::read(&this.inner));
+            drop(core::ptr::read(&this.deps));
+            drop(core::ptr::read(&this.symbol_cache));
+        }
+        outcome
+    }
+
+    /// Adds the library to the global symbol scope, as reopening it with `RTLD_GLOBAL` would.
+    ///
+    /// Its symbols become visible to [`dlsym_default`](crate::dlsym_default) and to the
+    /// relocation of libraries loaded afterwards. Promoting a global library does nothing;
+    /// like glibc, there is no way back to `RTLD_LOCAL`.
+    pub fn promote_global(&self) {
+        crate::lock_write!(MANAGER).promoted_name(self.shortname(), OpenFlags::RTLD_GLOBAL);
+    }
+
+    /// Gives up the handle for good, keeping the library mapped for the rest of the process.
+    ///
+    /// The library is marked `RTLD_NODELETE`, so no other handle can unload it either, and
+    /// the handle is leaked. Addresses of the library stay valid with a `'static` lifetime,
+    /// e.g. for function pointers registered with long-lived C subsystems.
+    pub fn leak(self) -> LeakedDylib {
+        crate::lock_write!(MANAGER).promoted_name(self.shortname(), OpenFlags::RTLD_NODELETE);
+        LeakedDylib {
+            lib: Box::leak(Box::new(self)),
+        }
+    }
+
+    /// Whether the library is in the global symbol scope.
+    pub fn is_global(&self) -> bool {
+        crate::lock_read!(MANAGER).in_global_scope(self.shortname())
+    }
+
+    fn do_unload(&mut self) -> UnloadOutcome {
         let mut removed_libs = Vec::new();
-        {
+        let outcome = {
             let mut lock = lock_write!(MANAGER);
-            let shortname = self.inner.shortname();
-            let Some(flags) = lock.flags(shortname) else {
-                return;
+            // Counted under the lock, so no handle for this library is created concurrently.
+            let handles = self
+                .inner
+                .user_data()
+                .handles
+                .fetch_sub(1, Ordering::Relaxed)
+                - 1;
+            let Some(key) = lock.registry_key(&self.inner) else {
+                // Not owned by the registry, so released with its last handle.
+                drop(lock);
+                if handles == 0 {
+                    unsafe { run_fini(&self.inner) };
+                }
+                return UnloadOutcome::StillReferenced;
             };
+            let flags = lock
+                .flags(&key)
+                .expect("registered library must have flags");
 
             if flags.is_nodelete() {
-                return;
+                return UnloadOutcome::NoDelete;
             }
 
-            let ref_count = unsafe { self.inner.core_ref().strong_count() };
-            let has_global = flags.is_global();
-            // Dylib ref in committed link_ctx + dylib ref in this handle's deps list
-            // + global ref (if present) + handle's inner ref.
-            debug_assert!(self.deps.is_some());
-            let threshold = 3 + has_global as usize;
-
             log::debug!(
-                "Drop ElfLibrary [{}], ref count: {}, threshold: {}",
+                "Drop ElfLibrary [{}], remaining handles: {}",
                 self.inner.name(),
-                ref_count,
-                threshold
+                handles
             );
 
-            if ref_count == threshold {
-                log::info!("Destroying dylib [{}]", self.inner.name());
-                removed_libs.push(self.inner.clone());
-
-                lock.remove(shortname);
-
-                // Check dependencies
-                if let Some(deps) = self.deps.as_ref() {
-                    for dep in deps.iter().skip(1) {
-                        let dep_shortname = dep.shortname();
-                        let Some(dep_flags) = lock.flags(dep_shortname) else {
+            if handles == 0 {
+                // The libraries this handle may release: itself and every unreferenced
+                // library of its searchlist. Dependencies come after their dependents there.
+                let searchlist = self
+                    .deps
+                    .as_deref()
+                    .unwrap_or(core::slice::from_ref(&self.inner));
+                // Keyed by registry key, which differs from the short name for copies that
+                // a newer version of their file replaced.
+                let keys = searchlist
+                    .iter()
+                    .map(|lib| lock.registry_key(lib))
+                    .collect::<Vec<_>>();
+                let mut group = BTreeSet::new();
+                group.insert(key.clone());
+                for (dep, dep_key) in searchlist.iter().zip(&keys).skip(1) {
+                    if let Some(dep_key) = dep_key
+                        && lock.flags(dep_key).is_some_and(|f| !f.is_nodelete())
+                        && dep.user_data().handles.load(Ordering::Relaxed) == 0
+                    {
+                        group.insert(dep_key.clone());
+                    }
+                }
+                // Keep whatever is still needed from outside the group. Members of a
+                // dependency cycle only need each other, so they are released together.
+                loop {
+                    let needed = group
+                        .iter()
+                        .filter(|name| lock.has_dependents_outside(name, &group))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    if needed.is_empty() {
+                        break;
+                    }
+                    for name in needed {
+                        group.remove(&name);
+                    }
+                }
+                if group.contains(&key) {
+                    for (lib, lib_key) in searchlist.iter().zip(keys) {
+                        let Some(lib_key) = lib_key.filter(|lib_key| group.contains(lib_key))
+                        else {
                             continue;
                         };
-                        if dep_flags.is_nodelete() {
-                            continue;
-                        }
-                        // Dylib ref in committed link_ctx + dylib ref in the owner deps list
-                        // + global ref (if present).
-                        let dep_threshold = 3 + dep_flags.is_global() as usize;
-
-                        if unsafe { dep.core_ref().strong_count() } == dep_threshold {
-                            log::info!("Destroying dylib [{}]", dep.name());
-                            removed_libs.push(dep.clone());
-                            lock.remove(dep_shortname);
-                        }
+                        ld_debug!(
+                            LIBS,
+                            info,
+                            "dlclose{}: Destroying dylib [{}]",
+                            crate::utils::ld_debug::ThreadTag,
+                            lib.name()
+                        );
+                        removed_libs.push(lib.clone());
+                        lock.remove(&lib_key);
+                    }
+                    UnloadOutcome::Unloaded {
+                        ran_atexit_handlers: false,
                     }
+                } else {
+                    UnloadOutcome::StillReferenced
                 }
+            } else {
+                UnloadOutcome::StillReferenced
             }
-        }
+        };
+        let mut ran = 0;
         for lib in removed_libs {
             let base = lib.base();
             let range = base..(base + lib.mapped_len());
-            finalize(base as *mut _, Some(range));
+            ran += finalize(base as *mut _, Some(range));
+            unsafe { run_fini(&lib) };
+        }
+        match outcome {
+            UnloadOutcome::Unloaded { .. } => UnloadOutcome::Unloaded {
+                ran_atexit_handlers: ran != 0,
+            },
+            outcome => outcome,
         }
     }
 }
 
+impl Drop for ElfLibrary {
+    fn drop(&mut self) {
+        let _ = self.do_unload();
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct PendingDylib {
     inner: Option<LoadedDylib>,
@@ -190,12 +349,23 @@ impl Default for GlobalMeta {
     }
 }
 
+/// The registry key of a library that a newer copy of its file replaced, see
+/// [`Manager::retire`]. No file name contains a NUL byte, so it never collides with one.
+fn retired_key(shortname: &str, base: usize) -> String {
+    format!("{shortname}\0retired@{base:#x}")
+}
+
 /// The global manager for all loaded dynamic libraries.
 pub(crate) struct Manager {
     /// Libraries that are visible to concurrent `dlopen` calls but are not yet
     /// committed to the dependency graph.
     pending: IndexMap<String, PendingDylib>,
-    /// Libraries available in the global symbol scope (RTLD_GLOBAL).
+    /// Libraries available in the global symbol scope (RTLD_GLOBAL), in lookup order.
+    ///
+    /// The main program comes first, followed by the libraries the host linker had loaded
+    /// and then every library in the order it entered the scope: when it was loaded with
+    /// `RTLD_GLOBAL`, or when a later `dlopen` promoted it. Entries are only ever appended
+    /// or removed, so the first definition of a symbol keeps winning, as in glibc.
     global: IndexMap<String, LoadedDylib>,
     /// Alias names that resolve to a canonical short name.
     aliases: HashMap<String, String>,
@@ -247,12 +417,22 @@ impl Manager {
         Some(canonical)
     }
 
+    /// Adds a library to the global symbol scope.
+    ///
+    /// Only committed libraries may enter the global scope: `global_find` serves lazy
+    /// bindings from other threads without taking part in an ongoing `dlopen`, so it must
+    /// never see a library whose relocation has not finished.
     pub(crate) fn add_global(&mut self, name: String, lib: LoadedDylib) {
         debug_assert!(
             !self.global.contains_key(&name),
             "Library [{}] is already in global scope",
             name
         );
+        debug_assert!(
+            self.committed_id(&name).is_some(),
+            "Library [{}] must be relocated before entering the global scope",
+            name
+        );
         log::trace!("Adding [{}] to global scope", name);
         self.global.insert(name, lib);
     }
@@ -276,7 +456,8 @@ impl Manager {
             )
             .expect("registry insert must not insert duplicate keys");
         self.adds += 1;
-        log::trace!("Registered [{}] in global manager", name);
+        bump_generation();
+        ld_debug!(LIBS, trace, "Registered [{}] in global manager", name);
     }
 
     fn add_pending_reservation(&mut self, name: String, flags: OpenFlags) {
@@ -339,6 +520,15 @@ impl Manager {
         self.aliases.insert(alias.to_owned(), canonical.to_owned());
     }
 
+    /// Makes the library reachable by its `DT_SONAME` when that differs from the file name.
+    fn add_soname_alias(&mut self, canonical: &str, lib: &LoadedDylib) {
+        if let Some(soname) = lib.user_data().soname.as_deref()
+            && soname != canonical
+        {
+            self.add_alias(canonical, soname);
+        }
+    }
+
     pub(crate) fn add_identity(&mut self, identity: FileIdentity, name: &str) {
         // Newest wins; identical inode implies same physical file.
         self.identities.insert(identity, name.to_owned());
@@ -358,6 +548,7 @@ impl Manager {
             panic!("Library is not registered");
         };
         self.subs += 1;
+        bump_generation();
         let res = self.global.shift_remove(shortname);
         debug_assert!(
             !was_committed || flags.is_global() == res.is_some(),
@@ -371,6 +562,95 @@ impl Manager {
         self.identities.retain(|_, v| v != shortname);
     }
 
+    /// Moves the committed library `shortname` to a key of its own, freeing the short name
+    /// for a newer copy of its file.
+    ///
+    /// The retired library stays registered, with its dependency edges, until its last
+    /// handle drops. It gives up its aliases and file identity to the newer copy.
+    pub(crate) fn retire(&mut self, shortname: &str) {
+        let Some(lib) = self
+            .committed_id(shortname)
+            .and_then(|id| self.link_ctx.get(id))
+        else {
+            return;
+        };
+        let retired = retired_key(shortname, lib.base());
+        log::trace!("Retiring [{}] as [{}]", shortname, retired);
+        let rename = |key: &String| {
+            if key == shortname {
+                retired.clone()
+            } else {
+                key.clone()
+            }
+        };
+        // The link context can not rename a key, so rebuild it, keeping the load order and
+        // the edges of every library.
+        let entries =
+            self.link_ctx
+                .load_order()
+                .map(|id| {
+                    let key = self
+                        .link_ctx
+                        .key(id)
+                        .expect("load_order entries must resolve to interned keys");
+                    let module = self
+                        .link_ctx
+                        .get(id)
+                        .cloned()
+                        .expect("load_order entries must resolve to committed modules");
+                    let mut meta = self
+                        .link_ctx
+                        .meta(id)
+                        .cloned()
+                        .expect("load_order entries must resolve to committed metadata");
+                    if key == shortname {
+                        meta.libnames.clear();
+                    }
+                    let direct_deps =
+                        self.link_ctx
+                            .direct_deps(id)
+                            .unwrap_or(&[])
+                            .iter()
+                            .map(|dep| {
+                                rename(self.link_ctx.key(*dep).expect(
+                                    "direct dependency id must resolve in global link context",
+                                ))
+                            })
+                            .collect::<Vec<_>>()
+                            .into_boxed_slice();
+                    (rename(key), module, direct_deps, meta)
+                })
+                .collect::<Vec<_>>();
+        self.link_ctx = LinkContext::new();
+        for (key, module, direct_deps, meta) in entries {
+            self.link_ctx
+                .insert_with_meta(key, module, direct_deps, meta)
+                .expect("retiring must not insert duplicate keys");
+        }
+        self.global = self
+            .global
+            .drain(..)
+            .map(|(key, lib)| (rename(&key), lib))
+            .collect();
+        self.aliases.retain(|_, canonical| canonical != shortname);
+        self.identities.retain(|_, name| name != shortname);
+    }
+
+    /// The key `lib` is registered under: its short name, or its retired key once a newer
+    /// copy of its file took the short name.
+    pub(crate) fn registry_key(&self, lib: &LoadedDylib) -> Option<String> {
+        let shortname = lib.shortname();
+        if self
+            .committed_id(shortname)
+            .and_then(|id| self.link_ctx.get(id))
+            .is_some_and(|registered| registered.base() == lib.base())
+        {
+            return Some(shortname.to_owned());
+        }
+        let retired = retired_key(shortname, lib.base());
+        self.committed_id(&retired).is_some().then_some(retired)
+    }
+
     #[inline]
     pub(crate) fn lookup<'a>(&'a self, name: &str) -> Option<LibraryLookup<'a>> {
         // Primary lookup by canonical shortname.
@@ -432,15 +712,28 @@ impl Manager {
             .and_then(|name| self.lookup(name))
     }
 
-    #[inline]
+    /// Returns the main executable with the global searchlist as its scope.
+    ///
+    /// The scope is the main executable's own dependency scope followed by every library
+    /// currently in the global scope, which is what a `dlopen(NULL)` handle searches.
     pub(crate) fn main_library(&self) -> Option<ElfLibrary> {
         let id = self.link_ctx.load_order().next()?;
         let lib = self.link_ctx.get(id)?.clone();
-        let deps = self.library_scope_by_id(id)?;
-        Some(ElfLibrary {
-            inner: lib,
-            deps: Some(deps),
-        })
+        let main_scope = self.library_scope_by_id(id)?;
+        let mut seen = BTreeSet::new();
+        let deps = main_scope
+            .iter()
+            .chain(self.global_values())
+            .filter(|lib| seen.insert(lib.shortname().to_owned()))
+            .cloned()
+            .collect::<Vec<_>>();
+        Some(ElfLibrary::new_handle(lib, Arc::from(deps)))
+    }
+
+    /// The key of the main program, which is always the first library registered.
+    pub(crate) fn main_shortname(&self) -> Option<String> {
+        let id = self.link_ctx.load_order().next()?;
+        self.link_ctx.key(id).cloned()
     }
 
     pub(crate) fn canonical_direct_deps(&self, lib: &LoadedDylib) -> Box<[String]> {
@@ -557,7 +850,7 @@ impl Manager {
                 .into_boxed_slice();
             let pending = self.pending.shift_remove(&key);
             let was_pending = pending.is_some();
-            let meta = pending
+            let mut meta = pending
                 .map(|lib| GlobalMeta {
                     flags: lib.flags,
                     libnames: lib.libnames,
@@ -566,15 +859,18 @@ impl Manager {
                     flags: normalized_flags(module.name(), flags),
                     libnames: Vec::new(),
                 });
+            meta.flags = dynamic_flags(&module, meta.flags);
             self.link_ctx
                 .insert_with_meta(key.clone(), module.clone(), direct_deps, meta.clone())
                 .expect("load merge must not insert duplicate keys");
             if !was_pending {
                 self.adds += 1;
             }
+            bump_generation();
             if let Some(identity) = module.user_data().file_identity {
                 self.add_identity(identity, &key);
             }
+            self.add_soname_alias(&key, &module);
             for alias in libc_compat_aliases(&key) {
                 self.add_alias(&key, alias);
             }
@@ -590,6 +886,24 @@ impl Manager {
         );
     }
 
+    /// Whether a library outside `group` depends on `shortname`.
+    pub(crate) fn has_dependents_outside(&self, shortname: &str, group: &BTreeSet<String>) -> bool {
+        let Some(id) = self.committed_id(shortname) else {
+            return false;
+        };
+        self.link_ctx.load_order().any(|other| {
+            other != id
+                && self
+                    .link_ctx
+                    .key(other)
+                    .is_some_and(|key| !group.contains(key))
+                && self
+                    .link_ctx
+                    .direct_deps(other)
+                    .is_some_and(|deps| deps.contains(&id))
+        })
+    }
+
     pub(crate) fn visible_contains(&self, name: &str) -> bool {
         self.canonical_name_owned(name)
             .is_some_and(|canonical| self.link_ctx.contains_key(&canonical))
@@ -634,12 +948,14 @@ impl Manager {
         let id = self.committed_id(&canonical)?;
         let deps = self.library_scope_by_id(id)?;
         let inner = self.link_ctx.get(id).cloned()?;
-        Some(ElfLibrary {
-            inner,
-            deps: Some(deps),
-        })
+        Some(ElfLibrary::new_handle(inner, deps))
     }
 
+    /// Adds the promotable bits of `flags` to an already loaded library.
+    ///
+    /// Only the library itself is promoted. Dependencies that were loaded earlier keep
+    /// their own flags, so an `RTLD_LOCAL` library stays out of the global scope even when
+    /// a dependent is later opened with `RTLD_GLOBAL`.
     pub(crate) fn promote(&mut self, shortname: &str, flags: OpenFlags) {
         let key = shortname.to_owned();
         let id = self.committed_id(&key).expect("Library must be registered");
@@ -652,8 +968,9 @@ impl Manager {
             if entry.flags.contains(promotable) {
                 false
             } else {
+                let was_global = entry.flags.is_global();
                 entry.flags |= promotable;
-                flags.is_global()
+                flags.is_global() && !was_global
             }
         };
         if add_global {
@@ -666,6 +983,11 @@ impl Manager {
         }
     }
 
+    pub(crate) fn in_global_scope(&self, name: &str) -> bool {
+        self.canonical_name_owned(name)
+            .is_some_and(|canonical| self.global.contains_key(&canonical))
+    }
+
     pub(crate) fn library_scope(&self, name: &str) -> Option<Arc<[LoadedDylib]>> {
         let canonical = self.canonical_name_owned(name)?;
         let id = self.committed_id(&canonical)?;
@@ -696,6 +1018,20 @@ pub(crate) static MANAGER: Lazy<RwLock<Manager>> = Lazy::new(|| {
     })
 });
 
+/// Bumped whenever a library is committed to or removed from [`MANAGER`].
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+fn bump_generation() {
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// The registry generation, for lookups cached outside the registry lock to tell whether
+/// they may be stale.
+#[inline]
+pub(crate) fn generation() -> usize {
+    GENERATION.load(Ordering::Acquire)
+}
+
 fn normalized_flags(name: &str, mut flags: OpenFlags) -> OpenFlags {
     if name.contains("libc")
         || name.contains("libpthread")
@@ -709,6 +1045,14 @@ fn normalized_flags(name: &str, mut flags: OpenFlags) -> OpenFlags {
     flags
 }
 
+/// Applies flags the object requests for itself through `DT_FLAGS_1`.
+fn dynamic_flags(lib: &LoadedDylib, mut flags: OpenFlags) -> OpenFlags {
+    if lib.user_data().is_df_global() {
+        flags |= OpenFlags::RTLD_GLOBAL;
+    }
+    flags
+}
+
 fn libc_compat_aliases(shortname: &str) -> &'static [&'static str] {
     match shortname {
         "libc.so.6" => &[
@@ -758,7 +1102,7 @@ pub(crate) fn register_loaded(lib: LoadedDylib, flags: OpenFlags, manager: &mut
     let name = lib.name();
     let is_main = name.is_empty();
     let shortname = lib.shortname().to_owned();
-    let flags = normalized_flags(name, flags);
+    let flags = dynamic_flags(&lib, normalized_flags(name, flags));
 
     log::debug!(
         "Registering loaded library: [{}] (full path: [{}]) flags: [{:?}]",
@@ -772,6 +1116,7 @@ pub(crate) fn register_loaded(lib: LoadedDylib, flags: OpenFlags, manager: &mut
     if let Some(identity) = lib.user_data().file_identity {
         manager.add_identity(identity, &shortname);
     }
+    manager.add_soname_alias(&shortname, &lib);
     for alias in libc_compat_aliases(&shortname) {
         manager.add_alias(&shortname, alias);
     }
@@ -782,57 +1127,79 @@ pub(crate) fn register_loaded(lib: LoadedDylib, flags: OpenFlags, manager: &mut
 
 /// Finds a symbol in the global search scope.
 ///
-/// Iterates through all libraries registered with `RTLD_GLOBAL` in the order they were loaded.
+/// Iterates through all libraries registered with `RTLD_GLOBAL` in the order they entered the
+/// global scope, see `Manager::global`.
+/// Libraries still being relocated by a concurrent `dlopen` are pending and never part of
+/// the global scope, so no symbol is resolved against a half-relocated library.
 pub(crate) unsafe fn global_find<'a, T>(name: &str) -> Option<crate::Symbol<'a, T>> {
     lock_read!(MANAGER).global_values().find_map(|lib| unsafe {
-        lib.get::<T>(name).map(|sym| {
+        super::loader::filtered_get::<T>(lib, name).map(|sym| {
             log::trace!(
                 "Lazy Binding: find symbol [{}] from [{}] in global scope ",
                 name,
                 lib.name()
             );
+            trace_resolution!(name, lib, "global");
             core::mem::transmute(sym)
         })
     })
 }
 
-/// Finds the next occurrence of a symbol after the specified address.
-pub(crate) unsafe fn next_find<'a, T>(addr: usize, name: &str) -> Option<crate::Symbol<'a, T>> {
+/// Finds a symbol whose name is not UTF-8 in the global search scope, see [`global_find`].
+pub(crate) fn global_find_bytes(name: &[u8]) -> Option<*const ()> {
+    super::loader::find_symbol_bytes(lock_read!(MANAGER).global_values(), name)
+}
+
+/// The registered libraries loaded after the one whose mapping contains `addr`.
+fn libs_after(addr: usize) -> Option<Vec<LoadedDylib>> {
     let lock = lock_read!(MANAGER);
     let libs = lock.all_values().collect::<Vec<_>>();
-    // Find the library containing the address
     let idx = libs.iter().position(|v| {
         let start = v.base();
         let end = start + v.mapped_len();
         (start..end).contains(&addr)
     })?;
+    Some(libs.into_iter().skip(idx + 1).collect())
+}
 
-    // Search in all subsequent libraries
-    libs.into_iter().skip(idx + 1).find_map(|lib| unsafe {
-        lib.get::<T>(name).map(|sym| {
+/// Finds a symbol whose name is not UTF-8 after the specified address, see [`next_find`].
+pub(crate) fn next_find_bytes(addr: usize, name: &[u8]) -> Option<*const ()> {
+    super::loader::find_symbol_bytes(&libs_after(addr)?, name)
+}
+
+/// Finds the next occurrence of a symbol after the specified address.
+pub(crate) unsafe fn next_find<'a, T>(addr: usize, name: &str) -> Option<crate::Symbol<'a, T>> {
+    libs_after(addr)?.into_iter().find_map(|lib| unsafe {
+        super::loader::filtered_get::<T>(&lib, name).map(|sym| {
             log::trace!(
                 "dlsym: find symbol [{}] from [{}] via RTLD_NEXT",
                 name,
                 lib.name()
             );
+            trace_resolution!(name, lib, "next");
             core::mem::transmute(sym)
         })
     })
 }
 
+/// Finds the library whose mapping contains `addr`.
+///
+/// Ranges of registered libraries can overlap, for example when a library handed to
+/// `from_mapped` declares a length that covers another one. The most recently registered
+/// of them wins then, so a reloaded image is reported rather than the one it replaced.
 pub(crate) fn addr2dso(addr: usize) -> Option<ElfLibrary> {
     log::trace!("addr2dso: addr [{:#x}]", addr);
     let manager = crate::lock_read!(MANAGER);
-    let entry = manager.all_values().find(|v| {
-        let start = v.base();
-        let end = start + v.mapped_len();
-        (start..end).contains(&addr)
-    })?;
+    let entry = manager
+        .all_values()
+        .filter(|v| {
+            let start = v.base();
+            let end = start + v.mapped_len();
+            (start..end).contains(&addr)
+        })
+        .last()?;
     let deps = manager.library_scope(entry.shortname())?;
-    Some(ElfLibrary {
-        inner: entry,
-        deps: Some(deps),
-    })
+    Some(ElfLibrary::new_handle(entry, deps))
 }
 
 fn register_atexit(
@@ -859,7 +1226,8 @@ unsafe impl Sync for Destructor {}
 
 static DESTRUCTORS: Lazy<RwLock<Vec<Destructor>>> = Lazy::new(|| RwLock::new(Vec::new()));
 
-fn finalize(dso_handle: *mut c_void, range: Option<core::ops::Range<usize>>) {
+/// Runs the matching destructors registered with `__cxa_atexit` and returns how many ran.
+pub(crate) fn finalize(dso_handle: *mut c_void, range: Option<core::ops::Range<usize>>) -> usize {
     let mut to_run = Vec::new();
     {
         let mut range = range;
@@ -898,9 +1266,11 @@ fn finalize(dso_handle: *mut c_void, range: Option<core::ops::Range<usize>>) {
             dso_handle
         );
     }
+    let ran = to_run.len();
     for destructor in to_run.into_iter().rev() {
         unsafe { (destructor.func)(destructor.arg) };
     }
+    ran
 }
 
 #[unsafe(no_mangle)]
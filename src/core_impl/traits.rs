@@ -1,4 +1,5 @@
-use alloc::string::String;
+use crate::Result;
+use alloc::{string::String, vec::Vec};
 
 pub trait AsFilename {
     fn as_filename(&self) -> &str;
@@ -49,3 +50,17 @@ impl AsFilename for std::ffi::OsString {
         self.to_str().expect("OsString must be valid UTF-8")
     }
 }
+
+/// A custom source of object file contents, used by
+/// [`ElfLibrary::dlopen_with_builder`](crate::ElfLibrary::dlopen_with_builder).
+///
+/// This allows loading objects that are not stored as plain ELF files, e.g. encrypted
+/// plugins or files on an overlay that has to be read through an API.
+pub trait Builder {
+    /// Returns the contents of the object at `path`.
+    ///
+    /// The same path candidates as for a regular `dlopen` are passed in turn. A candidate
+    /// that does not exist must be reported as the [`Error::IO`](crate::Error::IO) of reading
+    /// it, so that the search moves on to the next one.
+    fn create_object(path: &str) -> Result<Vec<u8>>;
+}
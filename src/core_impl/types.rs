@@ -1,6 +1,10 @@
-use alloc::{boxed::Box, ffi::CString, string::String, vec::Vec};
-use core::ffi::c_char;
+use alloc::{boxed::Box, collections::BTreeMap, ffi::CString, string::String, vec::Vec};
+use core::{
+    ffi::c_char,
+    sync::atomic::{AtomicBool, AtomicUsize},
+};
 use elf_loader::elf::ElfDyn;
+use hashbrown::{DefaultHashBuilder, HashMap};
 
 pub(crate) type LinkMap = crate::abi::debug::LinkMap;
 
@@ -9,12 +13,76 @@ pub(crate) static mut ARGV: *const *mut c_char = core::ptr::null();
 pub(crate) static mut ENVP: *const *const c_char = core::ptr::null();
 
 /// File identity information for detecting duplicate loads via different paths (e.g., symlinks).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Two identities are equal when they name the same file, by device and inode. Size and
+/// modification time are kept to tell whether that file was rewritten after being loaded,
+/// see [`FileIdentity::is_unchanged`].
+#[derive(Debug, Clone, Copy)]
 pub(crate) struct FileIdentity {
     /// Device ID where the file resides.
     pub(crate) dev: u64,
     /// Inode number of the file.
     pub(crate) ino: u64,
+    /// Size of the file in bytes.
+    pub(crate) size: u64,
+    /// Last modification time, seconds part.
+    pub(crate) mtime: i64,
+    /// Last modification time, nanoseconds part.
+    pub(crate) mtime_nsec: i64,
+}
+
+impl FileIdentity {
+    /// Whether `current` is the same file with the same contents as when `self` was taken.
+    pub(crate) fn is_unchanged(&self, current: &FileIdentity) -> bool {
+        self == current
+            && self.size == current.size
+            && (self.mtime, self.mtime_nsec) == (current.mtime, current.mtime_nsec)
+    }
+}
+
+impl PartialEq for FileIdentity {
+    fn eq(&self, other: &Self) -> bool {
+        (self.dev, self.ino) == (other.dev, other.ino)
+    }
+}
+
+impl Eq for FileIdentity {}
+
+impl core::hash::Hash for FileIdentity {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (self.dev, self.ino).hash(state);
+    }
+}
+
+/// A small cache of resolved symbol addresses by name.
+///
+/// The entries are dropped whenever the registry [generation](super::register::generation)
+/// changes, i.e. a library was loaded or unloaded since they were resolved.
+#[derive(Default)]
+pub(crate) struct SymbolCache {
+    generation: usize,
+    entries: HashMap<String, usize, DefaultHashBuilder>,
+}
+
+impl SymbolCache {
+    const CAPACITY: usize = 64;
+
+    pub(crate) fn get(&mut self, name: &str, generation: usize) -> Option<usize> {
+        if self.generation != generation {
+            self.entries.clear();
+            self.generation = generation;
+            return None;
+        }
+        self.entries.get(name).copied()
+    }
+
+    pub(crate) fn insert(&mut self, name: &str, addr: usize) {
+        // Starting over is cheaper than tracking use, and a hot loop refills it at once.
+        if self.entries.len() == Self::CAPACITY {
+            self.entries.clear();
+        }
+        self.entries.insert(name.into(), addr);
+    }
 }
 
 /// User data associated with a dynamic library, used for internal tracking and debugging information.
@@ -30,6 +98,53 @@ pub(crate) struct ExtraData {
     pub(crate) dynamic_table: Option<Box<[ElfDyn]>>,
     /// File identity (device + inode) for detecting duplicate loads.
     pub(crate) file_identity: Option<FileIdentity>,
+    /// The absolute path of the file the library was loaded from.
+    pub(crate) resolved_path: Option<String>,
+    /// The value of `DT_FLAGS_1`, or 0 if the entry is absent.
+    pub(crate) flags_1: usize,
+    /// The `DT_SONAME` of the library, if it declares one.
+    pub(crate) soname: Option<String>,
+    /// The number of live `ElfLibrary` handles whose root is this library.
+    pub(crate) handles: AtomicUsize,
+    /// The number of relocations outside the PLT, with `DT_RELR` entries unpacked.
+    pub(crate) relocations: usize,
+    /// The number of PLT relocations (`DT_JMPREL`).
+    pub(crate) plt_relocations: usize,
+    /// Whether the PLT relocations were left to lazy binding.
+    pub(crate) lazy_binding: AtomicBool,
+    /// Whether the host dynamic linker mapped the library rather than this crate.
+    pub(crate) host: bool,
+    /// Whether the constructors were skipped at load time and have not run yet.
+    pub(crate) init_deferred: AtomicBool,
+    /// Whether the destructors ran, early through `run_destructors` or on release.
+    pub(crate) fini_done: AtomicBool,
+    /// The `DT_FILTER` entries: the library's symbols are looked up in these filtees only.
+    pub(crate) filters: Vec<String>,
+    /// The `DT_AUXILIARY` entries: filtees consulted before the library's own symbols.
+    pub(crate) auxiliaries: Vec<String>,
+    /// The filtees named by `filters` and `auxiliaries` that were loaded, in lookup order.
+    pub(crate) filtees: spin::Once<Box<[crate::ElfLibrary]>>,
+    /// The `DT_VERSYM` index of the default definition of each name that also has hidden
+    /// versioned definitions, or `None` if all of them are hidden. Built on first lookup.
+    #[cfg(feature = "version")]
+    pub(crate) default_versions: spin::Once<BTreeMap<String, Option<u16>>>,
+}
+
+impl ExtraData {
+    #[inline]
+    pub(crate) fn is_noopen(&self) -> bool {
+        self.flags_1 & crate::abi::dynamic::DF_1_NOOPEN != 0
+    }
+
+    #[inline]
+    pub(crate) fn is_df_global(&self) -> bool {
+        self.flags_1 & crate::abi::dynamic::DF_1_GLOBAL != 0
+    }
+
+    #[inline]
+    pub(crate) fn is_filter(&self) -> bool {
+        !self.filters.is_empty() || !self.auxiliaries.is_empty()
+    }
 }
 
 impl core::fmt::Debug for ExtraData {
@@ -40,6 +155,21 @@ impl core::fmt::Debug for ExtraData {
         d.field("needed_libs", &self.needed_libs);
         d.field("dynamic_table", &self.dynamic_table);
         d.field("file_identity", &self.file_identity);
+        d.field("resolved_path", &self.resolved_path);
+        d.field("flags_1", &self.flags_1);
+        d.field("soname", &self.soname);
+        d.field("handles", &self.handles);
+        d.field("relocations", &self.relocations);
+        d.field("plt_relocations", &self.plt_relocations);
+        d.field("lazy_binding", &self.lazy_binding);
+        d.field("host", &self.host);
+        d.field("init_deferred", &self.init_deferred);
+        d.field("fini_done", &self.fini_done);
+        d.field("filters", &self.filters);
+        d.field("auxiliaries", &self.auxiliaries);
+        d.field("filtees", &self.filtees.get().map(|f| f.len()));
+        #[cfg(feature = "version")]
+        d.field("default_versions", &self.default_versions.get());
         d.finish()
     }
 }
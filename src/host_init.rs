@@ -1,9 +1,8 @@
-use crate::abi::auxv::{AT_BASE, AT_PHDR, AT_PHNUM};
+use crate::abi::auxv::{AT_BASE, AT_PAGESZ, AT_PHDR, AT_PHNUM, AT_SYSINFO_EHDR};
 use crate::api::dl_iterate_phdr::CDlPhdrInfo;
 use crate::utils::debug::GDBDebug;
 use crate::{
     OpenFlags, Result,
-    api::dl_iterate_phdr::CallBack,
     core_impl::{
         ARGC, ARGV, DylibExt, ENVP, ExtraData, LinkMap, LoadedDylib, MANAGER, register_loaded,
     },
@@ -31,28 +30,19 @@ fn get_debug_struct() -> Option<&'static mut GDBDebug> {
     None
 }
 
-/// A list of dynamic tags that contain absolute addresses that need to be recovered.
-/// These addresses are often modified by the dynamic linker (like glibc) to be absolute,
-/// but we need them to be relative to the base address for our own loader.
-const DT_ADDR_TAGS: &[ElfDynamicTag] = &[
-    ElfDynamicTag::PLTGOT,
+/// The dynamic tags whose values glibc rebases in place when it maps an object at a nonzero
+/// base, as `elf_get_dynamic_info` does. Other address entries are left link-time relative.
+const HOST_REBASED_TAGS: &[ElfDynamicTag] = &[
     ElfDynamicTag::HASH,
+    ElfDynamicTag::PLTGOT,
     ElfDynamicTag::STRTAB,
     ElfDynamicTag::SYMTAB,
     ElfDynamicTag::RELA,
-    ElfDynamicTag::INIT,
-    ElfDynamicTag::FINI,
     ElfDynamicTag::REL,
-    ElfDynamicTag::JMPREL,
-    ElfDynamicTag::INIT_ARRAY,
-    ElfDynamicTag::FINI_ARRAY,
     ElfDynamicTag::RELR,
-    ElfDynamicTag::GNU_HASH,
-    ElfDynamicTag::GNU_LIBLIST,
-    ElfDynamicTag::RELACOUNT,
+    ElfDynamicTag::JMPREL,
     ElfDynamicTag::VERSYM,
-    ElfDynamicTag::VERDEF,
-    ElfDynamicTag::VERNEED,
+    ElfDynamicTag::GNU_HASH,
 ];
 
 static ONCE: Once = Once::new();
@@ -66,10 +56,27 @@ unsafe fn get_r_debug() -> *mut GDBDebug {
     unsafe { find_r_debug(phdr_addr, phnum, base) }
 }
 
+/// The runtime page size, which is not always 4K (e.g. 16K/64K pages on aarch64).
+fn page_size() -> usize {
+    static PAGE_SIZE: Once<usize> = Once::new();
+    *PAGE_SIZE.call_once(|| match get_auxv(AT_PAGESZ) {
+        0 => 0x1000,
+        size => size,
+    })
+}
+
+/// Reads an auxiliary vector entry, from `/proc/self/auxv` or else through libc.
+///
+/// `/proc` is often missing in containers and sandboxes. `getauxval` reads the copy libc
+/// saved at startup and reports a missing entry as 0, like an exhausted vector does here.
 #[cfg(target_os = "linux")]
 fn get_auxv(target_type: usize) -> usize {
     let Ok(data) = crate::os::read_file("/proc/self/auxv") else {
-        return 0;
+        static WARN_NO_PROC: Once = Once::new();
+        WARN_NO_PROC.call_once(|| {
+            log::warn!("init: /proc/self/auxv is unavailable, falling back to getauxval")
+        });
+        return unsafe { libc::getauxval(target_type as _) } as usize;
     };
     let size = core::mem::size_of::<usize>();
     for chunk in data.chunks_exact(size * 2) {
@@ -178,8 +185,8 @@ fn init_host_debug(debug: &mut GDBDebug) {
     custom.tail = cur;
 }
 
-/// Recovers the dynamic table by making absolute addresses relative to the base address.
-/// This is necessary because some dynamic linkers (like glibc) modify the dynamic table in place.
+/// Recovers the dynamic table by making the entries glibc rebased in place relative to the
+/// base address again, so the copy reads like the table of any other object.
 unsafe fn recover_dynamic_table(dynamic_ptr: *const ElfDyn, base: usize) -> Vec<ElfDyn> {
     let mut count = 0;
     while unsafe { (*dynamic_ptr.add(count)).tag() } != ElfDynamicTag::NULL {
@@ -193,7 +200,7 @@ unsafe fn recover_dynamic_table(dynamic_ptr: *const ElfDyn, base: usize) -> Vec<
         .collect::<Vec<_>>();
 
     for entry in table.iter_mut() {
-        if DT_ADDR_TAGS.contains(&entry.tag()) && entry.value() > base {
+        if base != 0 && HOST_REBASED_TAGS.contains(&entry.tag()) {
             let old = entry.value();
             entry.set_value(entry.value() - base);
             log::trace!(
@@ -226,7 +233,10 @@ unsafe fn from_raw(
         return Ok(None);
     }
 
-    let mut user_data = ExtraData::default();
+    let mut user_data = ExtraData {
+        host: true,
+        ..ExtraData::default()
+    };
     let name_str = name.to_string_lossy().into_owned();
     user_data.c_name = Some(name);
 
@@ -257,7 +267,10 @@ unsafe fn from_raw(
     }
 
     // 3. Process phdrs and memory length
-    let (phdrs, mut len) = get_phdrs_and_len(base, extra.map(|e| e.0));
+    let Some((phdrs, mut len)) = get_phdrs_and_len(base, extra.map(|e| e.0)) else {
+        log::info!("from_raw: malformed program headers, skipping");
+        return Ok(None);
+    };
     let mut use_phdrs = phdrs;
 
     if let Some(table) = &user_data.dynamic_table {
@@ -285,7 +298,20 @@ unsafe fn from_raw(
         link_map.l_phnum = use_phdrs.len().min(u16::MAX as usize) as u16;
     }
 
-    len = (len + 0xfff) & !0xfff; // align to page size
+    let page_size = page_size();
+    let Some(rounded) = len
+        .checked_add(page_size - 1)
+        .map(|len| len & !(page_size - 1))
+        .filter(|&len| base.checked_add(len).is_some())
+    else {
+        log::info!(
+            "from_raw: mapping length {:#x} at [{:#x}] overflows, skipping",
+            len,
+            base
+        );
+        return Ok(None);
+    };
+    len = rounded;
 
     log::info!(
         "from_raw: calling RelocatedDylib::new_unchecked, len={:#x}",
@@ -312,25 +338,73 @@ unsafe fn from_raw(
     Ok(Some(lib))
 }
 
-fn get_phdrs_and_len(base: usize, extra: Option<&[ElfPhdr]>) -> (Vec<ElfPhdr>, usize) {
+/// Collects the program headers of a mapped object and the length of its mapping.
+///
+/// Without `extra`, the table is read through the ELF header, one entry at a time. Returns
+/// `None` if no `PT_LOAD` maps the file start, if `e_phnum` describes a table that extends
+/// past that segment, or if the extent of a `PT_LOAD` overflows, which only a corrupt or
+/// exotic object does.
+fn get_phdrs_and_len(base: usize, extra: Option<&[ElfPhdr]>) -> Option<(Vec<ElfPhdr>, usize)> {
     let phdrs = if let Some(extra) = extra {
         extra.to_vec()
     } else {
         let ehdr = unsafe { &*(base as *const ElfHeader) };
-        let phdrs = unsafe {
-            core::slice::from_raw_parts((base + ehdr.e_phoff()) as *const ElfPhdr, ehdr.e_phnum())
-        };
-        phdrs.to_vec()
+        let phoff = ehdr.e_phoff();
+        // Until the segment mapping the file start is found, only the page holding the ELF
+        // header is known to be mapped. From then on that segment bounds the table.
+        let mut mapped = page_size();
+        let mut bounded = false;
+        let mut phdrs = Vec::new();
+        for i in 0..ehdr.e_phnum() {
+            let start = i
+                .checked_mul(size_of::<ElfPhdr>())
+                .and_then(|at| at.checked_add(phoff))?;
+            let end = start.checked_add(size_of::<ElfPhdr>())?;
+            base.checked_add(end)?;
+            if end > mapped {
+                log::warn!(
+                    "Program header table at [{:#x}] is not covered by its first PT_LOAD",
+                    base
+                );
+                return None;
+            }
+            let phdr = unsafe { ((base + start) as *const ElfPhdr).read_unaligned() };
+            if !bounded && phdr.program_type() == ElfProgramType::LOAD && phdr.p_offset() == 0 {
+                bounded = true;
+                mapped = phdr.p_filesz();
+                if end > mapped {
+                    log::warn!(
+                        "Program header table at [{:#x}] is not covered by its first PT_LOAD",
+                        base
+                    );
+                    return None;
+                }
+            }
+            phdrs.push(phdr);
+        }
+        if !bounded {
+            log::warn!("No PT_LOAD maps the start of the object at [{:#x}]", base);
+            return None;
+        }
+        phdrs
     };
 
-    let len = phdrs
+    let mut len = 0;
+    for phdr in phdrs
         .iter()
         .filter(|phdr| phdr.program_type() == ElfProgramType::LOAD)
-        .map(|phdr| phdr.p_vaddr() + phdr.p_memsz())
-        .max()
-        .unwrap_or(0);
+    {
+        let Some(end) = phdr.p_vaddr().checked_add(phdr.p_memsz()) else {
+            log::warn!(
+                "PT_LOAD of the object at [{:#x}] ends past the address space",
+                base
+            );
+            return None;
+        };
+        len = len.max(end);
+    }
 
-    (phdrs, len)
+    Some((phdrs, len))
 }
 
 fn find_host_link_map(base: usize) -> *mut LinkMap {
@@ -349,7 +423,11 @@ fn find_host_link_map(base: usize) -> *mut LinkMap {
     null_mut()
 }
 
-type IterPhdr = extern "C" fn(callback: Option<CallBack>, data: *mut c_void) -> c_int;
+/// The callback type of the host's `dl_iterate_phdr`, which must not unwind into it.
+type HostCallBack =
+    unsafe extern "C" fn(info: *mut CDlPhdrInfo, size: usize, data: *mut c_void) -> c_int;
+
+type IterPhdr = extern "C" fn(callback: Option<HostCallBack>, data: *mut c_void) -> c_int;
 
 struct LinkMapIter {
     current: *mut LinkMap,
@@ -469,7 +547,7 @@ unsafe extern "C" fn callback(info: *mut CDlPhdrInfo, _size: usize, _data: *mut
             .wrapping_sub(info.dlpi_tls_data as usize)
     });
 
-    let lib = unsafe {
+    let Some(lib) = unsafe {
         from_raw(
             CStr::from_ptr(info.dlpi_name).to_owned(),
             base,
@@ -482,9 +560,16 @@ unsafe extern "C" fn callback(info: *mut CDlPhdrInfo, _size: usize, _data: *mut
             find_host_link_map(base),
         )
     }
-    .unwrap()
-    .expect("from_raw failed in callback");
+    .unwrap() else {
+        // A malformed object is left to the host and keeps the iteration going.
+        return 0;
+    };
 
+    let mut manager = crate::lock_write!(MANAGER);
+    if manager.visible_contains(lib.shortname()) {
+        log::debug!("Skip lib: [{}], it is already registered", lib.shortname());
+        return 0;
+    }
     log::info!(
         "Initialize lib: [{}] @ [{:#x}]",
         lib.shortname(),
@@ -493,25 +578,211 @@ unsafe extern "C" fn callback(info: *mut CDlPhdrInfo, _size: usize, _data: *mut
     register_loaded(
         lib,
         OpenFlags::RTLD_NODELETE | OpenFlags::RTLD_GLOBAL,
-        &mut *crate::lock_write!(MANAGER),
+        &mut manager,
     );
     0
 }
 
+/// Registers the vDSO from `AT_SYSINFO_EHDR` if the host's `dl_iterate_phdr` did not report
+/// it, as musl's does not. Unwinders find `__vdso_*` frames through it.
+fn register_vdso() {
+    let ehdr_addr = get_auxv(AT_SYSINFO_EHDR);
+    if ehdr_addr == 0 {
+        return;
+    }
+    let Some((phdrs, _)) = get_phdrs_and_len(ehdr_addr, None) else {
+        return;
+    };
+    // The vDSO is normally linked at 0, but nothing requires it.
+    let Some(base) = phdrs
+        .iter()
+        .find(|p| p.program_type() == ElfProgramType::LOAD && p.p_offset() == 0)
+        .map(|p| ehdr_addr.wrapping_sub(p.p_vaddr()))
+    else {
+        return;
+    };
+    if crate::lock_read!(MANAGER)
+        .all_values()
+        .any(|lib| lib.base() == base)
+    {
+        return;
+    }
+    let Some(dynamic_ptr) = phdrs
+        .iter()
+        .find(|p| p.program_type() == ElfProgramType::DYNAMIC)
+        .map(|p| base.wrapping_add(p.p_vaddr()) as *const ElfDyn)
+    else {
+        return;
+    };
+    let name = CString::new("linux-vdso.so.1").unwrap();
+    let Ok(Some(lib)) =
+        (unsafe { from_raw(name, base, dynamic_ptr, None, find_host_link_map(base)) })
+    else {
+        log::warn!("init: could not register the vDSO at [{:#x}]", base);
+        return;
+    };
+    log::info!("Initialize lib: [{}] @ [{:#x}]", lib.shortname(), base);
+    let mut manager = crate::lock_write!(MANAGER);
+    if !manager.visible_contains(lib.shortname()) {
+        register_loaded(
+            lib,
+            OpenFlags::RTLD_NODELETE | OpenFlags::RTLD_GLOBAL,
+            &mut manager,
+        );
+    }
+}
+
 #[ctor::ctor]
 fn init() {
     log::info!("init: starting initialization");
     ONCE.call_once(|| {
+        // `ONCE` lives in this image, but the manager may have been filled through another
+        // path already; registering the host libraries again would duplicate them.
+        if crate::lock_read!(MANAGER).main_shortname().is_some() {
+            log::info!("init: host libraries are already registered");
+            return;
+        }
         if let Some(debug) = get_debug_struct() {
             init_host_debug(debug);
             iterate_phdr(debug.map, |iter| {
                 iter(Some(callback), null_mut());
             });
+            register_vdso();
 
             // Publish the host libraries into the committed link graph.
             let mut lock = crate::lock_write!(MANAGER);
             lock.rebuild_link_ctx();
+        } else {
+            log::warn!("init: the host's r_debug was not found, host libraries are not registered");
         }
         log::info!("init: initialization complete");
     });
 }
+
+#[cfg(all(test, target_pointer_width = "64"))]
+mod tests {
+    use super::*;
+
+    const PT_LOAD: u32 = 1;
+    const PHDR_SIZE: usize = 56;
+
+    /// An ELF64 image laid out in memory the way a loaded object starts: an ELF header whose
+    /// program header table sits at `phoff`, holding one `PT_LOAD` per `(vaddr, memsz)`.
+    struct Image(Vec<u64>);
+
+    impl Image {
+        fn new(len: usize, phoff: usize, loads: &[(usize, usize)]) -> Self {
+            let mut image = Image(alloc::vec![0; len / 8]);
+            let bytes = image.bytes_mut();
+            bytes[..4].copy_from_slice(b"\x7fELF");
+            bytes[0x20..0x28].copy_from_slice(&phoff.to_ne_bytes());
+            bytes[0x36..0x38].copy_from_slice(&(PHDR_SIZE as u16).to_ne_bytes());
+            bytes[0x38..0x3a].copy_from_slice(&(loads.len() as u16).to_ne_bytes());
+            for (i, phdr) in loads.iter().map(|&load| load_phdr(load, len)).enumerate() {
+                let at = phoff + i * PHDR_SIZE;
+                bytes[at..at + PHDR_SIZE].copy_from_slice(&phdr);
+            }
+            image
+        }
+
+        fn bytes_mut(&mut self) -> &mut [u8] {
+            unsafe { core::slice::from_raw_parts_mut(self.0.as_mut_ptr().cast(), self.0.len() * 8) }
+        }
+
+        fn base(&self) -> usize {
+            self.0.as_ptr() as usize
+        }
+    }
+
+    /// The bytes of a `PT_LOAD` mapping the first `filesz` bytes of the file at `vaddr`.
+    fn load_phdr((vaddr, memsz): (usize, usize), filesz: usize) -> [u8; PHDR_SIZE] {
+        let mut phdr = [0; PHDR_SIZE];
+        phdr[..4].copy_from_slice(&PT_LOAD.to_ne_bytes());
+        phdr[16..24].copy_from_slice(&vaddr.to_ne_bytes());
+        phdr[32..40].copy_from_slice(&filesz.to_ne_bytes());
+        phdr[40..48].copy_from_slice(&memsz.to_ne_bytes());
+        phdr
+    }
+
+    fn to_phdr(bytes: [u8; PHDR_SIZE]) -> ElfPhdr {
+        unsafe { core::ptr::read_unaligned(bytes.as_ptr().cast()) }
+    }
+
+    #[test]
+    fn only_rebased_entries_are_recovered() {
+        let base = 0x1000;
+        let dynamic = [
+            ElfDyn::new(ElfDynamicTag::STRTAB, base + 0x300),
+            // Left alone by glibc, and larger than the base of a low mapping.
+            ElfDyn::new(ElfDynamicTag::INIT_ARRAY, 0x2000),
+            ElfDyn::new(ElfDynamicTag::NULL, 0),
+        ];
+        let table = unsafe { recover_dynamic_table(dynamic.as_ptr(), base) };
+        assert_eq!(table.len(), 3);
+        assert_eq!(table[0].value(), 0x300);
+        assert_eq!(table[1].value(), 0x2000);
+    }
+
+    #[test]
+    fn mapping_length_covers_every_load() {
+        let image = Image::new(0x400, 0x40, &[(0, 0x400), (0x2000, 0x1800)]);
+        let (phdrs, len) = get_phdrs_and_len(image.base(), None).unwrap();
+        assert_eq!(phdrs.len(), 2);
+        assert_eq!(len, 0x3800);
+    }
+
+    #[test]
+    fn table_offset_overflow_is_rejected() {
+        let mut image = Image::new(0x400, 0x40, &[(0, 0x400)]);
+        image.bytes_mut()[0x20..0x28].copy_from_slice(&(usize::MAX - 8).to_ne_bytes());
+        assert!(get_phdrs_and_len(image.base(), None).is_none());
+    }
+
+    #[test]
+    fn load_end_overflow_is_rejected() {
+        let image = Image::new(0x400, 0x40, &[(0, 0x400), (usize::MAX - 0xfff, 0x2000)]);
+        assert!(get_phdrs_and_len(image.base(), None).is_none());
+
+        let phdrs = [
+            to_phdr(load_phdr((0, 0x400), 0x400)),
+            to_phdr(load_phdr((0x1000, usize::MAX), 0)),
+        ];
+        assert!(get_phdrs_and_len(0, Some(&phdrs)).is_none());
+    }
+
+    #[test]
+    fn table_past_the_first_page_is_read_when_mapped() {
+        let page = page_size();
+        // The first entry maps the whole image and ends inside the first page; the second
+        // one straddles the page end.
+        let phoff = page - PHDR_SIZE - 8;
+        let image = Image::new(2 * page, phoff, &[(0, 2 * page), (0, 2 * page)]);
+        let (phdrs, len) = get_phdrs_and_len(image.base(), None).unwrap();
+        assert_eq!(phdrs.len(), 2);
+        assert_eq!(len, 2 * page);
+    }
+
+    #[test]
+    fn table_without_a_load_of_the_file_start_is_rejected() {
+        let mut image = Image::new(0x400, 0x40, &[(0, 0x400)]);
+        image.bytes_mut()[0x40 + 8..0x40 + 16].copy_from_slice(&0x1000usize.to_ne_bytes());
+        assert!(get_phdrs_and_len(image.base(), None).is_none());
+    }
+
+    #[test]
+    fn bogus_entry_count_stops_at_the_first_load() {
+        // The count claims the maximum, but the segment only maps the first two entries.
+        let mut image = Image::new(0x400, 0x40, &[(0, 0x400), (0x1000, 0x400)]);
+        image.bytes_mut()[0x40 + 32..0x40 + 40].copy_from_slice(&0xb0usize.to_ne_bytes());
+        image.bytes_mut()[0x38..0x3a].copy_from_slice(&u16::MAX.to_ne_bytes());
+        assert!(get_phdrs_and_len(image.base(), None).is_none());
+    }
+
+    #[test]
+    fn table_past_the_first_load_is_rejected() {
+        let mut image = Image::new(0x400, 0x40, &[(0, 0x400), (0x1000, 0x400)]);
+        // The segment mapping the file start now ends inside the table.
+        image.bytes_mut()[0x40 + 32..0x40 + 40].copy_from_slice(&0x50usize.to_ne_bytes());
+        assert!(get_phdrs_and_len(image.base(), None).is_none());
+    }
+}
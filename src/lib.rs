@@ -53,8 +53,12 @@ mod utils;
 
 use bitflags::bitflags;
 
-pub use crate::api::dlsym::{dlsym_default, dlsym_next};
-pub use crate::core_impl::{AsFilename, ElfLibrary};
+pub use crate::api::dlopen::{MissingDepPolicy, Progress};
+pub use crate::api::dlsym::{SymbolAddr, dlsym_default, dlsym_next};
+pub use crate::core_impl::{
+    AsFilename, Builder, ElfLibrary, LeakedDylib, LoadStats, Protection, SymbolBinding, SymbolInfo,
+    SymbolType, SymbolVisibility, UnloadOutcome,
+};
 pub use crate::error::Error;
 pub use elf_loader::image::Symbol;
 
@@ -70,8 +74,12 @@ bitflags! {
     #[derive(Clone, Copy, Debug)]
     pub struct OpenFlags:u32{
         /// Symbols in this library are not made available to resolve references in subsequently loaded libraries.
+        ///
+        /// This is the absence of `RTLD_GLOBAL`, so `RTLD_GLOBAL | RTLD_LOCAL` is global.
         const RTLD_LOCAL = 0;
         /// Perform lazy binding: resolve symbols only as they are executed.
+        ///
+        /// `dlopen` refuses it together with `RTLD_NOW`, which glibc would accept as `RTLD_NOW`.
         const RTLD_LAZY = 1;
         /// Resolve all symbols before `dlopen` returns.
         const RTLD_NOW = 2;
@@ -82,6 +90,9 @@ bitflags! {
         /// Make symbols in this library available for symbol resolution in subsequently loaded libraries.
         const RTLD_GLOBAL = 256;
         /// Do not unload the library during `dlclose`.
+        ///
+        /// Like in glibc, this applies to the opened library only. Its dependencies stay
+        /// loaded because it needs them, not because they are marked `RTLD_NODELETE`.
         const RTLD_NODELETE = 4096;
     }
 }
@@ -1,9 +1,9 @@
 use crate::core_impl::FileIdentity;
 use crate::{Error, Result};
-use alloc::boxed::Box;
 #[cfg(not(feature = "std"))]
 use alloc::string::ToString;
 use alloc::vec::Vec;
+use alloc::{borrow::ToOwned, boxed::Box, string::String};
 
 #[cfg(target_arch = "x86_64")]
 #[repr(C)]
@@ -234,7 +234,34 @@ pub(crate) fn get_file_inode(path: &str) -> Result<FileIdentity> {
         Ok(_) => Ok(FileIdentity {
             dev: stat_buf.st_dev as u64,
             ino: stat_buf.st_ino as u64,
+            size: stat_buf.st_size as u64,
+            mtime: stat_buf.st_mtime,
+            mtime_nsec: stat_buf.st_mtime_nsec as i64,
         }),
         Err(e) => Err(Error::from(e)),
     }
 }
+
+/// Makes `path` absolute by prefixing the current directory. Symbolic links are kept.
+pub(crate) fn absolute_path(path: &str) -> Option<String> {
+    if path.starts_with('/') {
+        return Some(path.to_owned());
+    }
+    let mut cwd = alloc::vec![0u8; 4096];
+    let len = unsafe {
+        syscalls::syscall2(
+            syscalls::Sysno::getcwd,
+            cwd.as_mut_ptr() as usize,
+            cwd.len(),
+        )
+    }
+    .ok()?;
+    // The returned length counts the terminating NUL.
+    cwd.truncate(len.checked_sub(1)?);
+    let mut absolute = String::from_utf8(cwd).ok()?;
+    if !absolute.ends_with('/') {
+        absolute.push('/');
+    }
+    absolute.push_str(path.strip_prefix("./").unwrap_or(path));
+    Some(absolute)
+}
@@ -17,5 +17,13 @@ cfg_if::cfg_if! {
         pub(crate) fn get_file_inode(_path: &str) -> crate::Result<FileIdentity> {
             Err(crate::Error::Unsupported)
         }
+        pub(crate) fn absolute_path(path: &str) -> Option<alloc::string::String> {
+            path.starts_with('/').then(|| alloc::borrow::ToOwned::to_owned(path))
+        }
     }
 }
+
+#[cfg(feature = "noexec-fallback")]
+mod noexec;
+#[cfg(feature = "noexec-fallback")]
+pub(crate) use noexec::NoexecMmap;
@@ -0,0 +1,67 @@
+use core::{ffi::c_void, ptr::NonNull};
+use elf_loader::{
+    Result,
+    os::{DefaultMmap, MapFlags, Mmap, ProtFlags},
+};
+
+/// Maps segments like [`DefaultMmap`], but copies executable segments into anonymous memory
+/// when the file itself may not be mapped executable, e.g. on a `noexec` mount.
+///
+/// The copies are private to the process, so the pages are no longer shared with other
+/// processes mapping the same library.
+pub(crate) struct NoexecMmap;
+
+impl Mmap for NoexecMmap {
+    unsafe fn mmap(
+        addr: Option<usize>,
+        len: usize,
+        prot: ProtFlags,
+        flags: MapFlags,
+        offset: usize,
+        fd: Option<isize>,
+        need_copy: &mut bool,
+    ) -> Result<NonNull<c_void>> {
+        let res = unsafe { DefaultMmap::mmap(addr, len, prot, flags, offset, fd, need_copy) };
+        if res.is_ok() || fd.is_none() || !prot.contains(ProtFlags::PROT_EXEC) {
+            return res;
+        }
+        if !matches!(
+            std::io::Error::last_os_error().raw_os_error(),
+            Some(libc::EPERM | libc::EACCES)
+        ) {
+            return res;
+        }
+        log::debug!(
+            "mmap: Executable file mapping refused, copying {:#x} bytes at offset {:#x}",
+            len,
+            offset
+        );
+        // Without a file the loader fills the mapping itself, like for in-memory objects.
+        unsafe { DefaultMmap::mmap(addr, len, prot, flags, offset, None, need_copy) }
+    }
+
+    unsafe fn mmap_anonymous(
+        addr: usize,
+        len: usize,
+        prot: ProtFlags,
+        flags: MapFlags,
+    ) -> Result<NonNull<c_void>> {
+        unsafe { DefaultMmap::mmap_anonymous(addr, len, prot, flags) }
+    }
+
+    unsafe fn munmap(addr: NonNull<c_void>, len: usize) -> Result<()> {
+        unsafe { DefaultMmap::munmap(addr, len) }
+    }
+
+    unsafe fn mprotect(addr: NonNull<c_void>, len: usize, prot: ProtFlags) -> Result<()> {
+        unsafe { DefaultMmap::mprotect(addr, len, prot) }
+    }
+
+    unsafe fn mmap_reserve(
+        addr: Option<usize>,
+        len: usize,
+        use_file: bool,
+    ) -> Result<NonNull<c_void>> {
+        unsafe { DefaultMmap::mmap_reserve(addr, len, use_file) }
+    }
+}
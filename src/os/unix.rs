@@ -1,6 +1,6 @@
 use crate::Result;
 use crate::core_impl::FileIdentity;
-use alloc::boxed::Box;
+use alloc::{boxed::Box, string::String};
 
 pub(crate) fn read_file(path: &str) -> Result<Box<[u8]>> {
     std::fs::read(path)
@@ -23,5 +23,17 @@ pub(crate) fn get_file_inode(path: &str) -> Result<FileIdentity> {
     Ok(FileIdentity {
         dev: metadata.dev(),
         ino: metadata.ino(),
+        size: metadata.size(),
+        mtime: metadata.mtime(),
+        mtime_nsec: metadata.mtime_nsec(),
     })
 }
+
+/// Makes `path` absolute by prefixing the current directory. Symbolic links are kept.
+pub(crate) fn absolute_path(path: &str) -> Option<String> {
+    std::path::absolute(path)
+        .ok()?
+        .into_os_string()
+        .into_string()
+        .ok()
+}
@@ -0,0 +1,46 @@
+//! Decompresses plugins shipped as gzip or zstd files before they are loaded.
+
+use crate::{Result, error::find_lib_error};
+use std::io::Read;
+
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZSTD_MAGIC: &[u8] = b"\x28\xb5\x2f\xfd";
+/// The largest decompressed object accepted, so that a small file can not exhaust memory.
+const MAX_DECOMPRESSED: u64 = 1 << 30;
+
+/// Reads `path` and decompresses it if it starts with a gzip or zstd magic.
+///
+/// Returns the decompressed object together with the name to register it under, which is
+/// `path` without its `.gz` or `.zst` extension. Returns `None` for any other file, and an
+/// error if the object decompresses to more than 1 GiB.
+pub(crate) fn decompress_file(path: &str) -> Result<Option<(Vec<u8>, &str)>> {
+    let Ok(header) = crate::os::read_file_limit(path, ZSTD_MAGIC.len()) else {
+        return Ok(None);
+    };
+    let (compressed, ext) = if header.starts_with(GZIP_MAGIC) {
+        (true, ".gz")
+    } else if header.starts_with(ZSTD_MAGIC) {
+        (false, ".zst")
+    } else {
+        return Ok(None);
+    };
+    let file = std::fs::File::open(path)?;
+    let mut bytes = Vec::new();
+    // One byte past the limit tells an object of exactly the limit from a larger one.
+    let limit = MAX_DECOMPRESSED + 1;
+    let res = if compressed {
+        flate2::read::GzDecoder::new(file)
+            .take(limit)
+            .read_to_end(&mut bytes)
+    } else {
+        zstd::stream::read::Decoder::new(file).and_then(|d| d.take(limit).read_to_end(&mut bytes))
+    };
+    res.map_err(|err| find_lib_error(format!("can not decompress [{}]: {}", path, err)))?;
+    if bytes.len() as u64 > MAX_DECOMPRESSED {
+        return Err(find_lib_error(format!(
+            "can not decompress [{}]: larger than {} bytes",
+            path, MAX_DECOMPRESSED
+        )));
+    }
+    Ok(Some((bytes, path.strip_suffix(ext).unwrap_or(path))))
+}
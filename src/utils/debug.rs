@@ -117,6 +117,30 @@ impl Drop for ExtraData {
     }
 }
 
+/// Finds the name of the library in the debugger's link map list that contains `addr`.
+#[cfg(feature = "init-watchdog")]
+pub(crate) fn link_map_name(addr: usize) -> Option<alloc::string::String> {
+    let custom_debug = DEBUG.lock();
+    if custom_debug.debug.is_null() {
+        return None;
+    }
+    let mut best: Option<&LinkMap> = None;
+    let mut cur = unsafe { (*custom_debug.debug).map };
+    while let Some(link_map) = unsafe { cur.as_ref() } {
+        let base = link_map.l_addr as usize;
+        if base <= addr && best.is_none_or(|best| best.l_addr as usize <= base) {
+            best = Some(link_map);
+        }
+        cur = link_map.l_next;
+    }
+    let name = best?.l_name;
+    (!name.is_null()).then(|| {
+        unsafe { CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
 unsafe fn call_debug_state(debug: &GDBDebug) {
     if let Some(brk) = debug.brk {
         brk();
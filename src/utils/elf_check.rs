@@ -0,0 +1,96 @@
+//! Checks the ELF header of untrusted input before any of it reaches `elf_loader`.
+
+use crate::{Result, error::find_lib_error};
+
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+const EI_CLASS: usize = 4;
+const EI_DATA: usize = 5;
+const EI_VERSION: usize = 6;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const EV_CURRENT: u8 = 1;
+#[cfg(target_arch = "x86_64")]
+const EM_HOST: u16 = 62;
+#[cfg(target_arch = "aarch64")]
+const EM_HOST: u16 = 183;
+#[cfg(target_arch = "riscv64")]
+const EM_HOST: u16 = 243;
+
+/// No legitimate object comes close; it only bounds the work done on garbage.
+const MAX_PHNUM: usize = 0x1000;
+
+fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[inline]
+pub(crate) fn is_elf_input(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x7fELF")
+}
+
+/// The start of the error for an ELF file built for another class or machine, which
+/// library searches treat as a miss.
+pub(crate) const WRONG_ARCH: &str = "wrong ELF class or machine";
+
+/// Whether the ELF header at the start of `header` was built for the host.
+pub(crate) fn is_host_object(header: &[u8]) -> bool {
+    header.len() >= 0x14
+        && header[EI_CLASS] == ELFCLASS64
+        && header[EI_DATA] == ELFDATA2LSB
+        && read_u16(header, 0x12) == EM_HOST
+}
+
+/// Rejects input that is not a 64-bit little-endian ELF of the current version.
+pub(crate) fn check_ident(bytes: &[u8]) -> Result<()> {
+    if !is_elf_input(bytes) {
+        return Err(find_lib_error("not an ELF file"));
+    }
+    if bytes.get(EI_CLASS) != Some(&ELFCLASS64) || bytes.get(EI_DATA) != Some(&ELFDATA2LSB) {
+        return Err(find_lib_error("not a 64-bit little-endian ELF"));
+    }
+    if bytes.get(EI_VERSION) != Some(&EV_CURRENT) {
+        return Err(find_lib_error("unknown ELF version"));
+    }
+    Ok(())
+}
+
+/// Validates the ELF header of `bytes` and the position of its program header table.
+///
+/// Everything the loader reads before it looks at the segments themselves is checked here,
+/// so malformed input is refused with an error instead of failing deep inside the loader.
+pub(crate) fn validate_elf_header(bytes: &[u8]) -> Result<()> {
+    check_ident(bytes)?;
+    if bytes.len() < EHDR_SIZE {
+        return Err(find_lib_error("truncated ELF header"));
+    }
+    if read_u32(bytes, 0x14) != EV_CURRENT as u32 {
+        return Err(find_lib_error("unknown ELF version"));
+    }
+    if (read_u16(bytes, 0x34) as usize) < EHDR_SIZE {
+        return Err(find_lib_error("invalid ELF header size"));
+    }
+    if read_u16(bytes, 0x36) as usize != PHDR_SIZE {
+        return Err(find_lib_error("invalid program header entry size"));
+    }
+    let phnum = read_u16(bytes, 0x38) as usize;
+    if phnum == 0 || phnum > MAX_PHNUM {
+        return Err(find_lib_error("invalid program header count"));
+    }
+    let phoff = read_u64(bytes, 0x20);
+    let table_end = usize::try_from(phoff)
+        .ok()
+        .and_then(|phoff| phoff.checked_add(phnum * PHDR_SIZE));
+    if phoff < EHDR_SIZE as u64 || table_end.is_none_or(|end| end > bytes.len()) {
+        return Err(find_lib_error("program headers lie outside the file"));
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use crate::Result;
 use crate::error::parse_ld_cache_error;
+use crate::utils::ld_debug::ld_debug;
 use alloc::boxed::Box;
 use alloc::string::String;
 use core::cmp::Ordering;
@@ -88,6 +89,9 @@ impl LdCache {
         Ok(cache)
     }
 
+    /// Finds the full path of `lib_name` for the current architecture.
+    ///
+    /// The path is complete, so callers open it as is instead of joining it to a directory.
     pub fn lookup(&self, lib_name: &str) -> Option<String> {
         if self.nlibs == 0 {
             return None;
@@ -132,7 +136,7 @@ impl LdCache {
                     while i < self.nlibs && self.get_name(i) == Some(name) {
                         if self.check_flags(i) {
                             if let Some(path) = self.get_path(i) {
-                                log::debug!("LD_CACHE match: {} -> {}", lib_name, path);
+                                ld_debug!(FILES, debug, "LD_CACHE match: {} -> {}", lib_name, path);
                                 return Some(path);
                             }
                         }
@@ -145,7 +149,7 @@ impl LdCache {
                 Ordering::Greater => left = mid + 1,
             }
         }
-        log::debug!("LD_CACHE not found: {}", lib_name);
+        ld_debug!(FILES, debug, "LD_CACHE not found: {}", lib_name);
         None
     }
 
@@ -185,7 +189,10 @@ impl LdCache {
                 return Some(String::from(s));
             }
         }
-        extract_str(&self.data[self.string_table_offset..], val_idx).map(String::from)
+        // A relative path would be opened against the working directory.
+        extract_str(&self.data[self.string_table_offset..], val_idx)
+            .filter(|s| s.starts_with('/'))
+            .map(String::from)
     }
 
     fn check_flags(&self, idx: usize) -> bool {
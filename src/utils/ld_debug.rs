@@ -0,0 +1,106 @@
+//! Runtime selection of loader tracing, in the spirit of glibc's `LD_DEBUG`.
+//!
+//! `DLOPEN_RS_DEBUG` holds a list of categories separated by commas, colons or spaces.
+//! Lines of an enabled category are logged at info level under the target
+//! `dlopen_rs::debug::<category>`; all other lines keep their usual level. `LD_DEBUG` itself
+//! is not read, since the host's dynamic linker acts on it as well.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use spin::Once;
+
+/// Libraries being searched, opened, registered and destroyed.
+pub(crate) const LIBS: u32 = 1 << 0;
+/// Relocation of newly loaded libraries.
+pub(crate) const RELOC: u32 = 1 << 1;
+/// Symbol lookups through the public API.
+pub(crate) const SYMBOLS: u32 = 1 << 2;
+/// Which library each symbol was bound to.
+pub(crate) const BINDINGS: u32 = 1 << 3;
+/// Files tried while searching for a library.
+pub(crate) const FILES: u32 = 1 << 4;
+
+const CATEGORIES: &[(&str, u32)] = &[
+    ("libs", LIBS),
+    ("reloc", RELOC),
+    ("symbols", SYMBOLS),
+    ("bindings", BINDINGS),
+    ("files", FILES),
+    ("all", LIBS | RELOC | SYMBOLS | BINDINGS | FILES),
+];
+
+static MASK: AtomicU32 = AtomicU32::new(0);
+static PARSED: Once = Once::new();
+
+/// Sets the enabled categories from the value of `DLOPEN_RS_DEBUG`, once per process.
+pub(crate) fn init(value: impl FnOnce() -> Option<&'static str>) {
+    PARSED.call_once(|| {
+        let Some(value) = value() else {
+            return;
+        };
+        let mut mask = 0;
+        for name in value.split([',', ':', ' ']).filter(|name| !name.is_empty()) {
+            match CATEGORIES.iter().find(|(category, _)| *category == name) {
+                Some((_, bits)) => mask |= bits,
+                None => log::warn!("DLOPEN_RS_DEBUG: unknown category [{}]", name),
+            }
+        }
+        MASK.store(mask, Ordering::Relaxed);
+    });
+}
+
+/// The `log` target of a category's lines.
+pub(crate) fn target(category: u32) -> &'static str {
+    match category {
+        LIBS => "dlopen_rs::debug::libs",
+        RELOC => "dlopen_rs::debug::reloc",
+        SYMBOLS => "dlopen_rs::debug::symbols",
+        BINDINGS => "dlopen_rs::debug::bindings",
+        _ => "dlopen_rs::debug::files",
+    }
+}
+
+#[inline]
+pub(crate) fn enabled(category: u32) -> bool {
+    MASK.load(Ordering::Relaxed) & category != 0
+}
+
+/// Names the calling thread in log lines, so that concurrent loads can be told apart.
+///
+/// Displays as `[name ThreadId(n)]`, or `[ThreadId(n)]` for an unnamed thread. Without
+/// `std` there are no thread names, and it displays as nothing.
+pub(crate) struct ThreadTag;
+
+impl core::fmt::Display for ThreadTag {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let thread = std::thread::current();
+        match thread.name() {
+            Some(name) => write!(f, "[{} {:?}]", name, thread.id()),
+            None => write!(f, "[{:?}]", thread.id()),
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Ok(())
+    }
+}
+
+/// Logs a line of a `DLOPEN_RS_DEBUG` category.
+///
+/// The line is logged at info level under `dlopen_rs::debug::<category>` if the category is
+/// enabled, and through the given `log` macro otherwise.
+macro_rules! ld_debug {
+    ($category:ident, $level:ident, $($arg:tt)+) => {
+        if $crate::utils::ld_debug::enabled($crate::utils::ld_debug::$category) {
+            log::info!(
+                target: $crate::utils::ld_debug::target($crate::utils::ld_debug::$category),
+                $($arg)+
+            );
+        } else {
+            log::$level!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use ld_debug;
@@ -1,3 +1,34 @@
+#[cfg(feature = "compression")]
+pub(crate) mod compression;
 pub(crate) mod debug;
+pub(crate) mod elf_check;
 pub(crate) mod ld_cache;
+pub(crate) mod ld_debug;
 pub(crate) mod linker_script;
+#[cfg(feature = "init-watchdog")]
+pub(crate) mod watchdog;
+
+/// Logs which library a symbol was resolved from, and through which scope.
+///
+/// Logs at debug level with the `trace-resolution` feature, and otherwise only if
+/// `DLOPEN_RS_DEBUG` enables `bindings`.
+macro_rules! trace_resolution {
+    ($name:expr, $lib:expr, $scope:expr $(,)?) => {
+        if cfg!(feature = "trace-resolution")
+            || $crate::utils::ld_debug::enabled($crate::utils::ld_debug::BINDINGS)
+        {
+            let lib = &$lib;
+            $crate::utils::ld_debug::ld_debug!(
+                BINDINGS,
+                debug,
+                "resolution: symbol [{}] -> [{}] base: {:#x} scope: {}",
+                $name,
+                lib.name(),
+                lib.base(),
+                $scope
+            );
+        }
+    };
+}
+
+pub(crate) use trace_resolution;
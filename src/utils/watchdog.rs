@@ -0,0 +1,140 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+use std::{
+    backtrace::Backtrace,
+    sync::{
+        OnceLock,
+        mpsc::{self, RecvTimeoutError, Sender},
+    },
+    time::Instant,
+};
+
+/// How long a constructor may run before it is reported, in milliseconds.
+static THRESHOLD_MS: AtomicU64 = AtomicU64::new(5000);
+
+/// Feeds the single watchdog thread, which is started by the first watched constructor.
+static WATCHDOG: OnceLock<Sender<Message>> = OnceLock::new();
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn set_threshold(threshold: Duration) {
+    THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+enum Message {
+    Start(Pending),
+    Done { id: usize },
+}
+
+struct Pending {
+    id: usize,
+    addr: usize,
+    threshold: Duration,
+    deadline: Instant,
+    reported: Arc<AtomicBool>,
+}
+
+/// Reports the constructor at `addr` if it is still running when the threshold passes.
+///
+/// The constructor is considered finished when the returned guard is dropped.
+pub(crate) fn watch(addr: usize) -> Guard {
+    let threshold = Duration::from_millis(THRESHOLD_MS.load(Ordering::Relaxed));
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let reported = Arc::new(AtomicBool::new(false));
+    let start = Instant::now();
+    let _ = sender().send(Message::Start(Pending {
+        id,
+        addr,
+        threshold,
+        deadline: start + threshold,
+        reported: reported.clone(),
+    }));
+    Guard {
+        id,
+        addr,
+        start,
+        reported,
+    }
+}
+
+fn sender() -> &'static Sender<Message> {
+    WATCHDOG.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("dlopen-init-watchdog".into())
+            .spawn(move || {
+                let mut pending: Vec<Pending> = Vec::new();
+                loop {
+                    let msg = match pending.iter().map(|p| p.deadline).min() {
+                        Some(deadline) => {
+                            let timeout = deadline.saturating_duration_since(Instant::now());
+                            match rx.recv_timeout(timeout) {
+                                Ok(msg) => Some(msg),
+                                Err(RecvTimeoutError::Timeout) => None,
+                                Err(RecvTimeoutError::Disconnected) => return,
+                            }
+                        }
+                        None => match rx.recv() {
+                            Ok(msg) => Some(msg),
+                            Err(_) => return,
+                        },
+                    };
+                    match msg {
+                        Some(Message::Start(watched)) => pending.push(watched),
+                        Some(Message::Done { id }) => pending.retain(|p| p.id != id),
+                        None => {
+                            let now = Instant::now();
+                            pending.retain(|p| {
+                                if p.deadline > now {
+                                    return true;
+                                }
+                                report(p);
+                                false
+                            });
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn the init watchdog thread");
+        tx
+    })
+}
+
+fn report(pending: &Pending) {
+    pending.reported.store(true, Ordering::Release);
+    let name = super::debug::link_map_name(pending.addr);
+    log::warn!(
+        "init watchdog: constructor {:#x} of [{}] has run for more than {:?}",
+        pending.addr,
+        name.as_deref().unwrap_or("unknown"),
+        pending.threshold,
+    );
+}
+
+pub(crate) struct Guard {
+    id: usize,
+    addr: usize,
+    start: Instant,
+    reported: Arc<AtomicBool>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(tx) = WATCHDOG.get() {
+            let _ = tx.send(Message::Done { id: self.id });
+        }
+        // The stuck thread can not be unwound from the watchdog, so the `dlopen` call that
+        // led into a reported constructor is captured here, once it returns.
+        if self.reported.load(Ordering::Acquire) {
+            log::warn!(
+                "init watchdog: constructor {:#x} finished after {:?}, called from:\n{}",
+                self.addr,
+                self.start.elapsed(),
+                Backtrace::force_capture()
+            );
+        }
+    }
+}
@@ -161,6 +161,22 @@ fn dladdr() {
     assert!(find.dylib().name() == lib.name());
 }
 
+#[test]
+fn dladdr_local_checks_own_mapping() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    let print = unsafe { lib.get::<fn(&str)>("print").unwrap() };
+    let addr = print.into_raw() as usize;
+    let find = lib.dladdr_local(addr).unwrap();
+    assert_eq!(find.symbol_name(), Some("print"));
+    assert_eq!(find.symbol_addr(), Some(addr));
+    assert!(
+        lib.dladdr_local(dladdr_local_checks_own_mapping as usize)
+            .is_none()
+    );
+}
+
 #[test]
 fn thread_local() {
     compile();
@@ -193,3 +209,595 @@ fn linker_script_as_needed() {
     let lib = ElfLibrary::dlopen(script_path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
     assert!(lib.name().contains("libexample.so"));
 }
+
+#[test]
+fn dlopen_with_progress_cancel() {
+    compile();
+    let path = lib_path("libpromotion.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+    let cancel_path = lib_dir.join("libprogress_cancel.so");
+    std::fs::copy(&path, &cancel_path).unwrap();
+    let cancel_path = cancel_path.to_str().unwrap();
+
+    let mut seen = Vec::new();
+    let res = ElfLibrary::dlopen_with_progress(cancel_path, OpenFlags::RTLD_NOW, |p| {
+        seen.push(p.name.to_string());
+        std::ops::ControlFlow::Break(())
+    });
+    assert!(res.is_err());
+    assert_eq!(seen.len(), 1);
+
+    // The cancelled load must have been rolled back.
+    assert!(ElfLibrary::dlopen(cancel_path, OpenFlags::RTLD_NOLOAD).is_err());
+    let mut reports = Vec::new();
+    let lib = ElfLibrary::dlopen_with_progress(cancel_path, OpenFlags::RTLD_NOW, |p| {
+        reports.push((p.current, p.total));
+        std::ops::ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert!(unsafe { lib.get::<fn(i32, i32) -> i32>("promotion_add").is_ok() });
+    // The total counts the libraries this load relocates, not the loaded ones it uses.
+    assert!(!reports.is_empty());
+    for (i, &(current, total)) in reports.iter().enumerate() {
+        assert_eq!((current, total), (i, reports.len()));
+    }
+}
+
+#[test]
+fn dlopen_with_progress_single_library() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+    let single_path = lib_dir.join("libprogress_single.so");
+    std::fs::copy(&path, &single_path).unwrap();
+    let single_path = single_path.to_str().unwrap();
+
+    // The callback runs with the registry unlocked, so it may look symbols up.
+    let mut reports = Vec::new();
+    let lib = ElfLibrary::dlopen_with_progress(single_path, OpenFlags::RTLD_NOW, |p| {
+        assert!(dlopen_rs::dlsym_default::<()>("malloc").is_ok());
+        reports.push((p.name.to_string(), p.current, p.total));
+        std::ops::ControlFlow::Continue(())
+    })
+    .unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].0.ends_with("libprogress_single.so"));
+    assert_eq!((reports[0].1, reports[0].2), (0, 1));
+    drop(lib);
+
+    let cancel_path = lib_dir.join("libprogress_single_cancel.so");
+    std::fs::copy(&path, &cancel_path).unwrap();
+    let cancel_path = cancel_path.to_str().unwrap();
+    let res = ElfLibrary::dlopen_with_progress(cancel_path, OpenFlags::RTLD_NOW, |_| {
+        std::ops::ControlFlow::Break(())
+    });
+    assert!(res.is_err());
+    assert!(ElfLibrary::dlopen(cancel_path, OpenFlags::RTLD_NOLOAD).is_err());
+}
+
+#[test]
+fn dl_iterate_phdr_stops_on_sentinel() {
+    use std::ffi::{c_int, c_void};
+
+    unsafe extern "C-unwind" fn stop_at_second(
+        _info: *mut dlopen_rs::api::CDlPhdrInfo,
+        _size: usize,
+        data: *mut c_void,
+    ) -> c_int {
+        let seen = unsafe { &mut *(data as *mut usize) };
+        *seen += 1;
+        if *seen == 2 { 42 } else { 0 }
+    }
+
+    compile();
+    let path = lib_path("libexample.so");
+    let _lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+
+    let mut seen = 0usize;
+    let ret = unsafe {
+        dlopen_rs::api::dl_iterate_phdr(Some(stop_at_second), &mut seen as *mut usize as _)
+    };
+    assert_eq!(ret, 42);
+    assert_eq!(seen, 2);
+
+    let mut visited = 0;
+    let res = ElfLibrary::dl_iterate_phdr(|_| {
+        visited += 1;
+        if visited == 2 {
+            Err(dlopen_rs::Error::Unsupported)
+        } else {
+            Ok(())
+        }
+    });
+    assert!(matches!(res, Err(dlopen_rs::Error::Unsupported)));
+    assert_eq!(visited, 2);
+}
+
+#[test]
+fn dladdr_tail_page() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    // The last byte of the mapping lies in the tail page of the last segment.
+    let last = lib.base() + lib.mapped_len() - 1;
+    let find = ElfLibrary::dladdr(last).unwrap();
+    assert_eq!(find.dylib().name(), lib.name());
+    assert!(
+        ElfLibrary::dladdr(lib.base() + lib.mapped_len())
+            .is_none_or(|info| info.dylib().name() != lib.name())
+    );
+
+    // The mapping length of a library the host registered is sized the same way.
+    let libc = ElfLibrary::dlopen("libc.so.6", OpenFlags::RTLD_NOW).unwrap();
+    assert!(libc.is_host_library());
+    let last = libc.base() + libc.mapped_len() - 1;
+    let find = ElfLibrary::dladdr(last).unwrap();
+    assert_eq!(find.dylib().name(), libc.name());
+}
+
+#[test]
+fn concurrent_dlopen_and_global_lookup() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+
+    let handles = (0..8)
+        .map(|i| {
+            let copy = lib_dir.join(format!("libexample_concurrent{i}.so"));
+            std::fs::copy(&path, &copy).unwrap();
+            std::thread::spawn(move || {
+                let lib = ElfLibrary::dlopen(
+                    copy.to_str().unwrap(),
+                    OpenFlags::RTLD_LAZY | OpenFlags::RTLD_GLOBAL,
+                )
+                .unwrap();
+                let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+                assert_eq!(add(1, 2), 3);
+                // Any global hit must come from a fully relocated library.
+                let add = dlopen_rs::dlsym_default::<fn(i32, i32) -> i32>("add").unwrap();
+                assert_eq!(add(2, 2), 4);
+                lib
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn close_reports_outcome() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+    let close_path = lib_dir.join("libexample_close.so");
+    std::fs::copy(&path, &close_path).unwrap();
+    let close_path = close_path.to_str().unwrap();
+
+    let first = ElfLibrary::dlopen(close_path, OpenFlags::RTLD_NOW).unwrap();
+    let second = ElfLibrary::dlopen(close_path, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(first.close(), dlopen_rs::UnloadOutcome::StillReferenced);
+    assert!(matches!(
+        second.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    assert!(ElfLibrary::dlopen(close_path, OpenFlags::RTLD_NOLOAD).is_err());
+}
+
+#[test]
+fn dlopen_from_binary_rejects_foreign_input() {
+    let text = b"this is not an elf file";
+    let mut elf32 = [0u8; 64];
+    elf32[..4].copy_from_slice(b"\x7fELF");
+    elf32[4] = 1; // ELFCLASS32
+    elf32[5] = 1; // ELFDATA2LSB
+    let mut big_endian = [0u8; 64];
+    big_endian[..4].copy_from_slice(b"\x7fELF");
+    big_endian[4] = 2; // ELFCLASS64
+    big_endian[5] = 2; // ELFDATA2MSB
+
+    for bytes in [&text[..], &elf32[..], &big_endian[..]] {
+        let res = ElfLibrary::dlopen_from_binary(bytes, "libforeign.so", OpenFlags::RTLD_NOW);
+        assert!(matches!(res, Err(dlopen_rs::Error::FindLibError { .. })));
+    }
+}
+
+#[test]
+fn dlopen_from_binary_rejects_malformed_headers() {
+    compile();
+    let valid = std::fs::read(lib_path("libexample.so")).unwrap();
+    let patched = |at: usize, value: &[u8]| {
+        let mut bytes = valid.clone();
+        bytes[at..at + value.len()].copy_from_slice(value);
+        bytes
+    };
+    let corpus = [
+        valid[..0].to_vec(),
+        valid[..4].to_vec(),
+        valid[..16].to_vec(),
+        valid[..63].to_vec(),
+        // A header whose program header table lies past the end.
+        valid[..64].to_vec(),
+        patched(6, &[0]),                       // EI_VERSION
+        patched(0x14, &0u32.to_le_bytes()),     // e_version
+        patched(0x20, &u64::MAX.to_le_bytes()), // e_phoff
+        patched(0x20, &8u64.to_le_bytes()),     // e_phoff inside the header
+        patched(0x34, &0u16.to_le_bytes()),     // e_ehsize
+        patched(0x36, &32u16.to_le_bytes()),    // e_phentsize
+        patched(0x38, &0u16.to_le_bytes()),     // e_phnum
+        patched(0x38, &u16::MAX.to_le_bytes()), // e_phnum
+        (0..4096).map(|i| (i * 31 % 251) as u8).collect(),
+    ];
+
+    for (i, bytes) in corpus.iter().enumerate() {
+        let res = ElfLibrary::dlopen_from_binary(bytes, "libmalformed.so", OpenFlags::RTLD_NOW);
+        assert!(
+            matches!(res, Err(dlopen_rs::Error::FindLibError { .. })),
+            "corpus entry {i} was not rejected cleanly"
+        );
+    }
+
+    let lib =
+        ElfLibrary::dlopen_from_binary(&valid, "libexample_valid_header.so", OpenFlags::RTLD_NOW)
+            .unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+}
+
+#[test]
+fn null_handle_searches_global_scope() {
+    compile();
+    let path = lib_path("libpromotion.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+    let global_path = lib_dir.join("libpromotion_null_handle.so");
+    std::fs::copy(&path, &global_path).unwrap();
+    let _lib = ElfLibrary::dlopen(
+        global_path.to_str().unwrap(),
+        OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL,
+    )
+    .unwrap();
+
+    let this = ElfLibrary::this();
+    let add = unsafe { this.get::<fn(i32, i32) -> i32>("promotion_add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+
+    unsafe {
+        let handle = dlopen_rs::api::dlopen(std::ptr::null(), OpenFlags::RTLD_NOW.bits() as _);
+        assert!(!handle.is_null());
+        let strlen = dlopen_rs::api::dlsym(handle, c"strlen".as_ptr());
+        assert!(!strlen.is_null());
+        dlopen_rs::api::dlclose(handle);
+    }
+}
+
+#[test]
+fn get_checked_rejects_oversized_type() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    unsafe {
+        let answer = lib.get_checked::<i32>("ANSWER").unwrap();
+        assert_eq!(**answer, 42);
+        assert!(lib.get_checked::<u64>("ANSWER").is_err());
+        // The unchecked lookup cannot tell the difference.
+        assert!(lib.get::<*mut u64>("ANSWER").is_ok());
+    }
+}
+
+#[test]
+fn dl_iterate_phdr_reports_main_first() {
+    compile();
+    let path = lib_path("libexample.so");
+    let _lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    let mut names = Vec::new();
+    ElfLibrary::dl_iterate_phdr(|info| {
+        names.push(info.name().to_string());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(names.first().map(String::as_str), Some(""));
+    assert_eq!(names.iter().filter(|name| name.is_empty()).count(), 1);
+}
+
+#[test]
+fn host_libraries_are_registered_once() {
+    let mut bases = Vec::new();
+    ElfLibrary::dl_iterate_phdr(|info| {
+        bases.push(info.base());
+        Ok(())
+    })
+    .unwrap();
+    let count = bases.len();
+    bases.sort_unstable();
+    bases.dedup();
+    assert_eq!(bases.len(), count);
+}
+
+#[test]
+fn lazy_and_now_together_are_rejected() {
+    compile();
+    let path = lib_path("libexample.so");
+    let err = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LAZY | OpenFlags::RTLD_NOW).unwrap_err();
+    assert!(
+        matches!(err, dlopen_rs::Error::FindLibError { .. }),
+        "{err}"
+    );
+    assert!(err.to_string().contains("RTLD_LAZY"), "{err}");
+}
+
+#[test]
+fn dlopen_path_rejects_non_utf8() {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt, path::Path};
+
+    compile();
+    let src = lib_path("libexample.so");
+    let dir = Path::new(&src).parent().unwrap();
+    let path = dir.join(OsStr::from_bytes(b"libexample_\xff.so"));
+    std::fs::copy(&src, &path).unwrap();
+    assert!(path.to_str().is_none());
+
+    // A lossy name could be shared by another path, so such paths are refused.
+    assert!(matches!(
+        ElfLibrary::dlopen_path(&path, OpenFlags::RTLD_NOW),
+        Err(dlopen_rs::Error::InvalidPath)
+    ));
+
+    let lib = ElfLibrary::dlopen_path(Path::new(&src), OpenFlags::RTLD_NOW).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+}
+
+#[test]
+fn dlopen_any_falls_back() {
+    compile();
+    let good = lib_path("libexample.so");
+    let missing = "/nonexistent/dlopen-rs/libmissing.so";
+
+    let lib = ElfLibrary::dlopen_any(&[missing, &good], OpenFlags::RTLD_NOW).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+
+    let other = "/nonexistent/dlopen-rs/libother.so";
+    let err = ElfLibrary::dlopen_any(&[missing, other], OpenFlags::RTLD_NOW).unwrap_err();
+    assert!(matches!(err, dlopen_rs::Error::FindLibError { .. }));
+    let msg = err.to_string();
+    assert!(msg.contains(missing));
+    assert!(msg.contains(other));
+}
+
+#[test]
+fn symbol_info_reports_metadata() {
+    use dlopen_rs::{SymbolBinding, SymbolType, SymbolVisibility};
+
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+
+    let answer = lib.symbol_info("ANSWER").unwrap();
+    assert_eq!(answer.kind, SymbolType::Object);
+    assert_eq!(answer.size, size_of::<i32>());
+    assert_eq!(answer.binding, SymbolBinding::Global);
+    assert_eq!(answer.visibility, SymbolVisibility::Default);
+    assert!(answer.library.ends_with("libexample.so"));
+    let sym = unsafe { lib.get::<*mut i32>("ANSWER").unwrap() };
+    assert_eq!(answer.address, *sym as usize);
+
+    let add = lib.symbol_info("add").unwrap();
+    assert_eq!(add.kind, SymbolType::Func);
+    assert!(add.size > 0);
+    assert!(lib.symbol_info("no_such_symbol").is_none());
+}
+
+#[test]
+fn dlopen_with_builder_decrypts() {
+    struct XorBuilder;
+
+    impl dlopen_rs::Builder for XorBuilder {
+        fn create_object(path: &str) -> dlopen_rs::Result<Vec<u8>> {
+            let mut bytes = std::fs::read(path)?;
+            if path.ends_with("_xor.so") {
+                bytes.iter_mut().for_each(|b| *b ^= 0x5a);
+            }
+            Ok(bytes)
+        }
+    }
+
+    compile();
+    let path = lib_path("libexample.so");
+    let encrypted = lib_path("libexample_xor.so");
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(
+        &encrypted,
+        bytes.iter().map(|b| b ^ 0x5a).collect::<Vec<_>>(),
+    )
+    .unwrap();
+    assert!(ElfLibrary::dlopen(&encrypted, OpenFlags::RTLD_NOW).is_err());
+
+    let lib =
+        ElfLibrary::dlopen_with_builder::<XorBuilder>(&encrypted, OpenFlags::RTLD_NOW).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+}
+
+#[test]
+fn dlclose_reports_invalid_handles() {
+    use std::ffi::CString;
+
+    compile();
+    let path = lib_path("libexample.so");
+    let lib_dir = PathBuf::from(&path).parent().unwrap().to_path_buf();
+    let close_path = lib_dir.join("libexample_dlclose.so");
+    let nodelete_path = lib_dir.join("libexample_dlclose_nodelete.so");
+    std::fs::copy(&path, &close_path).unwrap();
+    std::fs::copy(&path, &nodelete_path).unwrap();
+    let close_path = CString::new(close_path.to_str().unwrap()).unwrap();
+    let nodelete_path = CString::new(nodelete_path.to_str().unwrap()).unwrap();
+
+    unsafe {
+        let flags = OpenFlags::RTLD_NOW.bits() as _;
+        let handle = dlopen_rs::api::dlopen(close_path.as_ptr(), flags);
+        assert!(!handle.is_null());
+        assert_eq!(dlopen_rs::api::dlclose(handle), 0);
+        assert_ne!(dlopen_rs::api::dlclose(handle), 0);
+        assert_ne!(dlopen_rs::api::dlclose(std::ptr::null()), 0);
+
+        let flags = (OpenFlags::RTLD_NOW | OpenFlags::RTLD_NODELETE).bits() as _;
+        let handle = dlopen_rs::api::dlopen(nodelete_path.as_ptr(), flags);
+        assert!(!handle.is_null());
+        assert_eq!(dlopen_rs::api::dlclose(handle), 0);
+    }
+    // The RTLD_NODELETE library is still loaded.
+    assert!(ElfLibrary::dlopen(nodelete_path.to_str().unwrap(), OpenFlags::RTLD_NOLOAD).is_ok());
+}
+
+#[test]
+fn get_cached_matches_get() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    unsafe {
+        let add = lib.get::<fn(i32, i32) -> i32>("add").unwrap();
+        for _ in 0..3 {
+            let cached = lib.get_cached::<fn(i32, i32) -> i32>("add").unwrap();
+            assert_eq!(*cached as usize, *add as usize);
+            assert_eq!(cached(1, 2), 3);
+        }
+        let answer = lib.get_cached::<*const i32>("ANSWER").unwrap();
+        assert_eq!(**answer, 42);
+        // Clones share the cache; misses are still reported.
+        let clone = lib.clone();
+        assert!(clone.get_cached::<fn()>("no_such_symbol").is_err());
+        assert_eq!(*clone.get_cached::<*const i32>("ANSWER").unwrap(), *answer);
+        // Lookups stay right once another load has changed the registry.
+        let other = ElfLibrary::dlopen(lib_path("libpromotion.so"), OpenFlags::RTLD_NOW).unwrap();
+        let cached = lib.get_cached::<fn(i32, i32) -> i32>("add").unwrap();
+        assert_eq!(*cached as usize, *add as usize);
+        drop(other);
+    }
+}
+
+#[test]
+fn dladdr_reports_symbol_and_library() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    let addr = *add as usize;
+
+    let info = ElfLibrary::dladdr(addr).unwrap();
+    assert_eq!(info.library_name(), lib.name());
+    assert_eq!(info.library_base(), lib.base());
+    assert_eq!(info.symbol_name(), Some("add"));
+    assert_eq!(info.symbol_addr(), Some(addr));
+    // An address inside the function maps back to it as well.
+    let inner = ElfLibrary::dladdr(addr + 1).unwrap();
+    assert_eq!(inner.symbol_name(), Some("add"));
+}
+
+/// Needs `DLOPEN_RS_NOEXEC_DIR` to name a writable directory on a `noexec` mount, e.g. a
+/// tmpfs mounted with `-o noexec`.
+#[cfg(feature = "noexec-fallback")]
+#[test]
+fn dlopen_from_noexec_mount() {
+    let Some(dir) = std::env::var_os("DLOPEN_RS_NOEXEC_DIR") else {
+        eprintln!("skipping noexec test because DLOPEN_RS_NOEXEC_DIR is unset");
+        return;
+    };
+    compile();
+    let path = std::path::Path::new(&dir).join("libexample_noexec.so");
+    std::fs::copy(lib_path("libexample.so"), &path).unwrap();
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 2), 3);
+}
+
+#[test]
+fn host_libraries_are_flagged() {
+    compile();
+    let libc = ElfLibrary::dlopen("libc.so.6", OpenFlags::RTLD_NOW).unwrap();
+    assert!(libc.is_host_library());
+
+    let lib = ElfLibrary::dlopen(lib_path("libexample.so"), OpenFlags::RTLD_NOW).unwrap();
+    assert!(!lib.is_host_library());
+}
+
+#[test]
+fn c_entry_points_contain_panics() {
+    use dlopen_rs::api::{CDlPhdrInfo, dl_iterate_phdr, dlerror};
+    use std::ffi::{c_int, c_void};
+
+    unsafe extern "C-unwind" fn explode(
+        _info: *mut CDlPhdrInfo,
+        _size: usize,
+        _data: *mut c_void,
+    ) -> c_int {
+        panic!("callback panic");
+    }
+
+    compile();
+    let ret = unsafe { dl_iterate_phdr(Some(explode), std::ptr::null_mut()) };
+    assert_eq!(ret, -1);
+    assert!(!unsafe { dlerror() }.is_null());
+    // The registry is still usable afterwards.
+    assert!(ElfLibrary::dlopen(lib_path("libexample.so"), OpenFlags::RTLD_NOW).is_ok());
+}
+
+#[test]
+fn no_writable_executable_segments() {
+    compile();
+    let lib = ElfLibrary::dlopen(lib_path("libexample.so"), OpenFlags::RTLD_NOW).unwrap();
+    let segments = lib.segment_protections();
+    assert!(
+        segments
+            .iter()
+            .any(|(_, prot)| prot.contains(dlopen_rs::Protection::EXEC))
+    );
+    assert!(!lib.has_writable_executable_segment(), "{segments:?}");
+    for (range, _) in &segments {
+        assert!(range.start >= lib.base() && range.start < range.end);
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn dlopen_compressed() {
+    compile();
+    let path = lib_path("libexample.so");
+    for (tool, ext) in [("gzip", "gz"), ("zstd", "zst")] {
+        let name = lib_path(&format!("libexample_{ext}.so"));
+        let compressed = format!("{name}.{ext}");
+        let output = match std::process::Command::new(tool)
+            .arg("-c")
+            .arg(&path)
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            _ => {
+                eprintln!("skipping {tool} compressed library test because {tool} failed");
+                continue;
+            }
+        };
+        std::fs::write(&compressed, &output.stdout).unwrap();
+
+        let lib = ElfLibrary::dlopen(&compressed, OpenFlags::RTLD_NOW).unwrap();
+        assert_eq!(lib.name(), name);
+        let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+        assert_eq!(add(1, 2), 3);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn dl_iterate_phdr_reports_vdso() {
+    compile();
+    let mut vdso_phnum = None;
+    ElfLibrary::dl_iterate_phdr(|info| {
+        if info.name().contains("vdso") {
+            vdso_phnum = Some(info.phdrs().len());
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert!(vdso_phnum.is_some_and(|phnum| phnum > 0));
+}
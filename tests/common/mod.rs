@@ -0,0 +1,66 @@
+//! Helpers shared by the tests that compile C libraries on the fly.
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+pub fn target_dir() -> PathBuf {
+    option_env!("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("target"))
+}
+
+pub fn has_command(program: &str) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .output()
+        .map(|out| out.status.success())
+        .unwrap_or(false)
+}
+
+/// Creates a scratch directory for one test, apart from those of the other test binaries.
+pub fn test_work_dir(name: &str) -> PathBuf {
+    let dir = target_dir()
+        .join(format!("{}-tests", env!("CARGO_CRATE_NAME")))
+        .join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Compiles `source` into `dir/lib<name>.so`, linking against the given
+/// libraries from the same directory and passing `extra` to the compiler driver ahead
+/// of them.
+pub fn build_c_lib(
+    dir: &Path,
+    name: &str,
+    source: &str,
+    needed: &[&str],
+    extra: &[&str],
+) -> PathBuf {
+    let src = dir.join(format!("{name}.c"));
+    let out = dir.join(format!("lib{name}.so"));
+    fs::write(&src, source).unwrap();
+
+    let mut cmd = Command::new("cc");
+    cmd.arg("-shared")
+        .arg("-fPIC")
+        .arg(&src)
+        .arg("-o")
+        .arg(&out)
+        .arg(format!("-L{}", dir.display()))
+        .arg("-Wl,-rpath,$ORIGIN");
+    cmd.args(extra);
+    for lib in needed {
+        cmd.arg(format!("-l{lib}"));
+    }
+    assert!(
+        cmd.status()
+            .expect("failed to compile test library")
+            .success(),
+        "failed to compile lib{name}.so"
+    );
+    out
+}
@@ -0,0 +1,535 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[test]
+fn soname_differs_from_file_name() {
+    if !has_command("cc") {
+        eprintln!("skipping SONAME test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("soname");
+    let dep = build_c_lib(
+        &dir,
+        "snfile",
+        "int soname_value(void) { return 5; }\n",
+        &[],
+        &["-Wl,-soname,libsnreal.so.1"],
+    );
+    // DT_NEEDED of the root records the SONAME, which does not exist on disk.
+    let root = build_c_lib(
+        &dir,
+        "snroot",
+        r#"
+int soname_value(void);
+int root_value(void) { return soname_value(); }
+"#,
+        &["snfile"],
+        &[],
+    );
+
+    let dep = ElfLibrary::dlopen(dep.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(dep.shortname(), "libsnfile.so");
+    assert_eq!(dep.soname(), Some("libsnreal.so.1"));
+
+    let root = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let value = unsafe { root.get::<extern "C" fn() -> i32>("root_value").unwrap() };
+    assert_eq!(value(), 5);
+}
+
+#[test]
+fn resolved_dependencies_report_paths() {
+    if !has_command("cc") {
+        eprintln!("skipping resolved dependency test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("resolved-deps");
+    let dep = build_c_lib(
+        &dir,
+        "rdep",
+        "int rdep_value(void) { return 4; }\n",
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "rroot",
+        r#"
+int rdep_value(void);
+int rroot_value(void) { return rdep_value(); }
+"#,
+        &["rdep"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let resolved = lib.resolved_dependencies();
+    let (_, path) = resolved
+        .iter()
+        .find(|(needed, _)| *needed == "librdep.so")
+        .expect("librdep.so must be reported");
+    assert_eq!(
+        fs::canonicalize(path).unwrap(),
+        fs::canonicalize(&dep).unwrap()
+    );
+}
+
+#[test]
+fn replaced_file_is_loaded_again() {
+    if !has_command("cc") {
+        eprintln!("skipping replaced file test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("replaced-file");
+    let path = build_c_lib(
+        &dir,
+        "replaced",
+        "int replaced_value(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+    let old = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let old_value = unsafe { old.get::<extern "C" fn() -> i32>("replaced_value").unwrap() };
+    assert_eq!(old_value(), 1);
+
+    // Build the new version aside and rename it over the old file, like an installer would.
+    let staging = test_work_dir("replaced-file-staging");
+    let new = build_c_lib(
+        &staging,
+        "replaced",
+        "int replaced_value(void) { return 22; }\n",
+        &[],
+        &[],
+    );
+    fs::rename(&new, &path).unwrap();
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let new_value = unsafe { lib.get::<extern "C" fn() -> i32>("replaced_value").unwrap() };
+    assert_eq!(new_value(), 22);
+    // The old handle keeps the old mapping alive.
+    assert_eq!(old_value(), 1);
+
+    // Each copy is unloaded by its own last handle.
+    assert!(matches!(
+        old.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    assert_eq!(new_value(), 22);
+    assert!(matches!(
+        lib.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    assert!(ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOLOAD).is_err());
+}
+
+#[test]
+fn pie_executable_interpreter_is_not_a_dependency() {
+    if !has_command("cc") {
+        eprintln!("skipping PIE executable test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("pie-executable");
+    let src = dir.join("pie.c");
+    let exe = dir.join("pie");
+    fs::write(
+        &src,
+        r#"
+int pie_value(void) { return 17; }
+int main(void) { return pie_value(); }
+"#,
+    )
+    .unwrap();
+    let status = Command::new("cc")
+        .args(["-fPIE", "-pie", "-rdynamic"])
+        .arg(&src)
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("failed to compile test executable");
+    assert!(status.success(), "failed to compile the PIE executable");
+
+    let lib = ElfLibrary::dlopen(exe.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    // PT_INTERP names the dynamic loader, which only DT_NEEDED entries could pull in.
+    assert!(
+        lib.needed_libs()
+            .iter()
+            .all(|needed| !needed.contains("ld-"))
+    );
+    assert!(
+        lib.resolved_dependencies()
+            .iter()
+            .all(|(needed, _)| !needed.contains("ld-"))
+    );
+    let value = unsafe { lib.get::<extern "C" fn() -> i32>("pie_value").unwrap() };
+    assert_eq!(value(), 17);
+}
+
+#[test]
+fn wrong_machine_file_is_skipped_during_search() {
+    if !has_command("cc") {
+        eprintln!("skipping multiarch search test because cc is unavailable");
+        return;
+    }
+
+    let first = test_work_dir("multiarch-first");
+    let second = test_work_dir("multiarch-second");
+    // Left over from an earlier run, it would stand in the way of the link below.
+    let _ = fs::remove_file(first.join("libarchhelper.so"));
+    let helper = build_c_lib(
+        &second,
+        "archhelper",
+        "int arch_helper(void) { return 64; }\n",
+        &[],
+        &[],
+    );
+    let second_str = second.to_str().unwrap();
+    // The runpath is $ORIGIN first, then the second directory.
+    let root = build_c_lib(
+        &first,
+        "archroot",
+        "int arch_helper(void);\nint arch_root(void) { return arch_helper(); }\n",
+        &["archhelper"],
+        &[
+            &format!("-L{second_str}"),
+            &format!("-Wl,-rpath,{second_str}"),
+        ],
+    );
+
+    // A same-named library for i386 shadows the right one in the first directory.
+    let mut foreign = fs::read(&helper).unwrap();
+    foreign[0x12..0x14].copy_from_slice(&3u16.to_le_bytes());
+    fs::write(first.join("libarchhelper.so"), foreign).unwrap();
+
+    let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let arch_root = unsafe { lib.get::<extern "C" fn() -> i32>("arch_root").unwrap() };
+    assert_eq!(arch_root(), 64);
+    let (_, path) = lib
+        .resolved_dependencies()
+        .into_iter()
+        .find(|(needed, _)| *needed == "libarchhelper.so")
+        .unwrap();
+    assert!(path.starts_with(second_str), "{path}");
+}
+
+#[test]
+fn symlinks_in_two_directories_share_one_load() {
+    if !has_command("cc") {
+        eprintln!("skipping symlink dedup test because cc is unavailable");
+        return;
+    }
+
+    let root = test_work_dir("symlink-dirs");
+    let (real, first, second) = (root.join("real"), root.join("first"), root.join("second"));
+    for dir in [&real, &first, &second] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    let foo = build_c_lib(
+        &real,
+        "symfoo",
+        "int symfoo_loads;\n__attribute__((constructor)) static void symfoo_init(void) { symfoo_loads++; }\nint *symfoo_counter(void) { return &symfoo_loads; }\n",
+        &[],
+        &["-Wl,-soname,libsymfoo.so.1"],
+    );
+    for dir in [&first, &second] {
+        let link = dir.join("libsymfoo.so.1");
+        let _ = fs::remove_file(&link);
+        std::os::unix::fs::symlink(&foo, &link).unwrap();
+    }
+    let user_source = |name: &str| {
+        format!(
+            "int *symfoo_counter(void);\nint *{name}_counter(void) {{ return symfoo_counter(); }}\n"
+        )
+    };
+    let user_a = build_c_lib(
+        &first,
+        "symusera",
+        &user_source("symusera"),
+        &[":libsymfoo.so.1"],
+        &[],
+    );
+    let user_b = build_c_lib(
+        &second,
+        "symuserb",
+        &user_source("symuserb"),
+        &[":libsymfoo.so.1"],
+        &[],
+    );
+
+    let a = ElfLibrary::dlopen(user_a.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let b = ElfLibrary::dlopen(user_b.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    unsafe {
+        let counter_a = a
+            .get::<extern "C" fn() -> *const i32>("symusera_counter")
+            .unwrap()();
+        let counter_b = b
+            .get::<extern "C" fn() -> *const i32>("symuserb_counter")
+            .unwrap()();
+        assert_eq!(counter_a, counter_b);
+        assert_eq!(*counter_a, 1);
+    }
+}
+
+#[test]
+fn dlopen_from_binary_loads_dependencies_from_disk() {
+    if !has_command("cc") {
+        eprintln!("skipping in-memory dependency test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("from-binary-deps");
+    build_c_lib(
+        &dir,
+        "binhelper",
+        "int bin_helper(void) { return 5; }\n",
+        &[],
+        &[],
+    );
+    let plugin = build_c_lib(
+        &dir,
+        "binplugin",
+        "#include <string.h>\nint bin_helper(void);\nint bin_plugin(const char *s) { return (int)strlen(s) + bin_helper(); }\n",
+        &["binhelper"],
+        &[],
+    );
+    let bytes = fs::read(&plugin).unwrap();
+
+    // libc is already loaded; the helper is only on disk, next to the plugin's path.
+    let lib = ElfLibrary::dlopen_from_binary(&bytes, plugin.to_str().unwrap(), OpenFlags::RTLD_NOW)
+        .unwrap();
+    let bin_plugin = unsafe {
+        lib.get::<extern "C" fn(*const std::ffi::c_char) -> i32>("bin_plugin")
+            .unwrap()
+    };
+    assert_eq!(bin_plugin(c"abc".as_ptr()), 8);
+}
+
+/// Builds `lib<prefix>_top.so -> lib<prefix>_mid.so -> lib<prefix>_bottom.so`, where only
+/// the top library names the directory of the bottom one, through `top_dtags`.
+fn build_search_chain(prefix: &str, top_dtags: &str, mid_dtags: &str) -> PathBuf {
+    let dir = test_work_dir(&format!("{prefix}-chain"));
+    let hidden = dir.join("hidden");
+    fs::create_dir_all(&hidden).unwrap();
+    let bottom = format!("{prefix}_bottom");
+    let mid = format!("{prefix}_mid");
+    build_c_lib(
+        &hidden,
+        &bottom,
+        "int chain_bottom(void) { return 9; }\n",
+        &[],
+        &[],
+    );
+    build_c_lib(
+        &dir,
+        &mid,
+        "int chain_bottom(void);\nint chain_mid(void) { return chain_bottom(); }\n",
+        &[&bottom],
+        &[mid_dtags, &format!("-L{}", hidden.display())],
+    );
+    build_c_lib(
+        &dir,
+        &format!("{prefix}_top"),
+        "int chain_mid(void);\nint chain_top(void) { return chain_mid(); }\n",
+        &[&mid],
+        &[top_dtags, &format!("-Wl,-rpath,{}", hidden.display())],
+    )
+}
+
+#[test]
+fn runpath_is_not_inherited_but_rpath_is() {
+    if !has_command("cc") {
+        eprintln!("skipping search path inheritance test because cc is unavailable");
+        return;
+    }
+
+    // DT_RUNPATH of the top library does not apply to the dependencies of its dependency.
+    let top = build_search_chain(
+        "runpath",
+        "-Wl,--enable-new-dtags",
+        "-Wl,--disable-new-dtags",
+    );
+    assert!(ElfLibrary::dlopen(&top, OpenFlags::RTLD_NOW).is_err());
+
+    // DT_RPATH does, since the middle library has no DT_RUNPATH.
+    let top = build_search_chain(
+        "rpath",
+        "-Wl,--disable-new-dtags",
+        "-Wl,--disable-new-dtags",
+    );
+    let lib = ElfLibrary::dlopen(&top, OpenFlags::RTLD_NOW).unwrap();
+    let chain_top = unsafe { lib.get::<extern "C" fn() -> i32>("chain_top").unwrap() };
+    assert_eq!(chain_top(), 9);
+
+    // Unless the middle library has DT_RUNPATH, which hides the whole chain's DT_RPATH.
+    let top = build_search_chain(
+        "midrunpath",
+        "-Wl,--disable-new-dtags",
+        "-Wl,--enable-new-dtags",
+    );
+    assert!(ElfLibrary::dlopen(&top, OpenFlags::RTLD_NOW).is_err());
+}
+
+#[test]
+fn dlopen_at_loads_from_directory_fd() {
+    use std::os::fd::AsRawFd;
+
+    if !has_command("cc") {
+        eprintln!("skipping directory fd test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("dlopen-at");
+    build_c_lib(
+        &dir,
+        "at_plugin",
+        "int at_plugin(void) { return 21; }\n",
+        &[],
+        &[],
+    );
+    let dir_handle = fs::File::open(&dir).unwrap();
+    let dir_fd = dir_handle.as_raw_fd();
+
+    let lib = ElfLibrary::dlopen_at(dir_fd, "libat_plugin.so", OpenFlags::RTLD_NOW).unwrap();
+    let at_plugin = unsafe { lib.get::<extern "C" fn() -> i32>("at_plugin").unwrap() };
+    assert_eq!(at_plugin(), 21);
+
+    assert_eq!(
+        Path::new(lib.name()),
+        fs::canonicalize(dir.join("libat_plugin.so")).unwrap()
+    );
+    let again = ElfLibrary::dlopen_at(dir_fd, "libat_plugin.so", OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(again.base(), lib.base());
+
+    // A symbolic link in the directory can not lead outside of it.
+    let escape_link = dir.join("escape");
+    let _ = fs::remove_file(&escape_link);
+    std::os::unix::fs::symlink("/", &escape_link).unwrap();
+    for escape in [
+        "../dlopen-at/libat_plugin.so",
+        "/lib/libc.so.6",
+        "escape/lib/libc.so.6",
+        "..",
+    ] {
+        assert!(matches!(
+            ElfLibrary::dlopen_at(dir_fd, escape, OpenFlags::RTLD_NOW),
+            Err(dlopen_rs::Error::InvalidPath)
+        ));
+    }
+
+    // The same file name in another directory is not mistaken for the loaded library.
+    let other_dir = test_work_dir("dlopen-at-other");
+    build_c_lib(
+        &other_dir,
+        "at_plugin",
+        "int at_plugin(void) { return 42; }\n",
+        &[],
+        &[],
+    );
+    let other_handle = fs::File::open(&other_dir).unwrap();
+    assert!(
+        ElfLibrary::dlopen_at(
+            other_handle.as_raw_fd(),
+            "libat_plugin.so",
+            OpenFlags::RTLD_NOW
+        )
+        .is_err()
+    );
+    assert_eq!(at_plugin(), 21);
+}
+
+#[test]
+fn duplicate_needed_entries_load_once() {
+    if !has_command("cc") {
+        eprintln!("skipping duplicate DT_NEEDED test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("duplicate-needed");
+    for name in ["dupa", "dupb"] {
+        build_c_lib(
+            &dir,
+            name,
+            &format!("int {name}(void) {{ return 1; }}\n"),
+            &[],
+            &[],
+        );
+    }
+    let root = build_c_lib(
+        &dir,
+        "dup_root",
+        "int dup_root(void) { return 2; }\n",
+        &["dupa", "dupb"],
+        &["-Wl,--no-as-needed"],
+    );
+    // The linker never writes the same DT_NEEDED twice, so rename the second entry in the
+    // dynamic string table to the first.
+    let mut bytes = fs::read(&root).unwrap();
+    let from = b"libdupb.so\0";
+    let at = bytes
+        .windows(from.len())
+        .position(|window| window == from)
+        .unwrap();
+    bytes[at..at + from.len()].copy_from_slice(b"libdupa.so\0");
+    let patched = dir.join("libdup_patched.so");
+    fs::write(&patched, bytes).unwrap();
+
+    let lib = ElfLibrary::dlopen(&patched, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(lib.needed_libs(), ["libdupa.so", "libdupa.so"]);
+    let mut copies = 0;
+    ElfLibrary::dl_iterate_phdr(|info| {
+        if info.name().ends_with("/libdupa.so") {
+            copies += 1;
+        }
+        assert!(!info.name().ends_with("/libdupb.so"));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(copies, 1);
+}
+
+#[test]
+fn resolved_path_reports_file_found_by_search() {
+    if !has_command("cc") {
+        eprintln!("skipping resolved path test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("resolved-path");
+    let dep = build_c_lib(
+        &dir,
+        "rpathdep",
+        "int rpathdep_value(void) { return 3; }\n",
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "rpathroot",
+        r#"
+int rpathdep_value(void);
+int rpathroot_value(void) { return rpathdep_value(); }
+"#,
+        &["rpathdep"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(&root, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(
+        Path::new(lib.resolved_path().unwrap()),
+        std::path::absolute(&root).unwrap()
+    );
+    // The dependency was found through the root's `$ORIGIN` runpath.
+    let by_soname = ElfLibrary::dlopen("librpathdep.so", OpenFlags::RTLD_NOW).unwrap();
+    let resolved = by_soname.resolved_path().unwrap();
+    assert!(resolved.starts_with('/'));
+    assert_eq!(Path::new(resolved), std::path::absolute(&dep).unwrap());
+}
@@ -1,4 +1,4 @@
-use dlopen_rs::{ElfLibrary, OpenFlags, dlsym_default, dlsym_next};
+use dlopen_rs::{ElfLibrary, OpenFlags, SymbolAddr, dlsym_default, dlsym_next};
 use std::env::consts;
 use std::path::PathBuf;
 use std::sync::OnceLock;
@@ -100,3 +100,18 @@ fn test_dlsym() {
     let print = unsafe { lib.get::<fn(&str)>("print").unwrap() };
     print("dlopen-rs: hello world");
 }
+
+#[test]
+fn symbol_addresses_compare() {
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_GLOBAL | OpenFlags::RTLD_NOW).unwrap();
+
+    let next = SymbolAddr::from(unsafe { dlsym_next::<fn(i32, i32) -> i32>("add").unwrap() });
+    let direct = SymbolAddr::from(unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() });
+
+    // The override search reached libexample.so, not the local add.
+    assert_eq!(next, direct);
+    assert_ne!(next, add as *const ());
+    assert_eq!(next, next.as_ptr() as usize);
+}
@@ -14,3 +14,18 @@ fn glibc_234_compat_sonames_resolve_to_libc() {
         );
     }
 }
+
+#[cfg(feature = "version")]
+#[test]
+fn libc_symbol_versions_include_base_version() {
+    let Ok(lib) = ElfLibrary::dlopen("libc.so.6", OpenFlags::RTLD_NOW) else {
+        eprintln!("skipping symbol version test because libc.so.6 is unavailable");
+        return;
+    };
+    let versions = lib.symbol_versions();
+    assert!(
+        versions.iter().any(|v| v == "libc.so.6"),
+        "missing base version in {versions:?}"
+    );
+    assert!(versions.iter().any(|v| v.starts_with("GLIBC_2.")));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::ffi::{c_char, c_int};
+
+#[test]
+fn constructors_receive_init_args() {
+    if !has_command("cc") {
+        eprintln!("skipping init argument test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("constructor-args");
+    let lib = build_c_lib(
+        &dir,
+        "init_args",
+        r#"
+#include <string.h>
+static int seen_argc = -1;
+static int seen_arg1 = 0;
+__attribute__((constructor)) static void record(int argc, char **argv, char **envp) {
+    seen_argc = argc;
+    seen_arg1 = argc > 1 && strcmp(argv[1], "--plugin-test") == 0;
+}
+int recorded_argc(void) { return seen_argc; }
+int recorded_arg1(void) { return seen_arg1; }
+"#,
+        &[],
+        &[],
+    );
+
+    let argv: &'static [*mut c_char; 4] = Box::leak(Box::new([
+        c"host".as_ptr().cast_mut(),
+        c"--plugin-test".as_ptr().cast_mut(),
+        c"last".as_ptr().cast_mut(),
+        std::ptr::null_mut(),
+    ]));
+    let envp: &'static [*const c_char; 2] =
+        Box::leak(Box::new([c"DLOPEN_RS_TEST=1".as_ptr(), std::ptr::null()]));
+    unsafe { ElfLibrary::set_init_args(3, argv.as_ptr(), envp.as_ptr()) };
+
+    let lib = ElfLibrary::dlopen(&lib, OpenFlags::RTLD_NOW).unwrap();
+    let recorded_argc = unsafe {
+        lib.get::<extern "C" fn() -> c_int>("recorded_argc")
+            .unwrap()
+    };
+    let recorded_arg1 = unsafe {
+        lib.get::<extern "C" fn() -> c_int>("recorded_arg1")
+            .unwrap()
+    };
+    assert_eq!(recorded_argc(), 3);
+    assert_eq!(recorded_arg1(), 1);
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "init-watchdog")]
+
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{sync::Mutex, time::Duration};
+
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl log::Log for Capture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn slow_constructor_is_reported() {
+    if !has_command("cc") {
+        eprintln!("skipping init watchdog test because cc is unavailable");
+        return;
+    }
+    log::set_logger(&Capture).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let dir = test_work_dir("slow-constructor");
+    let out = build_c_lib(
+        &dir,
+        "slow_init",
+        r#"
+#include <unistd.h>
+int slow_init_done;
+__attribute__((constructor)) static void slow_init(void) {
+    usleep(500 * 1000);
+    slow_init_done = 1;
+}
+"#,
+        &[],
+        &[],
+    );
+
+    ElfLibrary::set_init_watchdog(Duration::from_millis(50));
+    let lib = ElfLibrary::dlopen(out.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    // The constructor was only reported, not interrupted.
+    let done = unsafe { *lib.get::<*const i32>("slow_init_done").unwrap() };
+    assert_eq!(unsafe { *done }, 1);
+
+    let warnings = WARNINGS.lock().unwrap();
+    assert!(
+        warnings
+            .iter()
+            .any(|msg| msg.contains("init watchdog") && msg.contains("libslow_init.so")),
+        "{warnings:?}"
+    );
+}
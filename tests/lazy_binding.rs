@@ -0,0 +1,67 @@
+#![cfg(any(target_arch = "aarch64", target_arch = "x86_64"))]
+
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{path::Path, process::Command};
+
+/// Finds the GOT offset of the `JUMP_SLOT` relocation for `symbol`.
+fn jump_slot_offset(lib: &Path, symbol: &str) -> usize {
+    let output = Command::new("readelf")
+        .arg("-rW")
+        .arg(lib)
+        .output()
+        .expect("failed to run readelf");
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    let line = text
+        .lines()
+        .find(|line| line.contains("JUMP_SLOT") && line.contains(symbol))
+        .unwrap_or_else(|| panic!("no JUMP_SLOT relocation for {symbol}"));
+    let offset = line.split_whitespace().next().unwrap();
+    usize::from_str_radix(offset, 16).unwrap()
+}
+
+#[test]
+fn lazy_plt_slot_is_bound_on_first_call() {
+    if !has_command("cc") || !has_command("readelf") {
+        eprintln!("skipping lazy PLT test because cc or readelf is unavailable");
+        return;
+    }
+    if std::env::var_os("LD_BIND_NOW").is_some() {
+        eprintln!("skipping lazy PLT test because LD_BIND_NOW is set");
+        return;
+    }
+
+    let dir = test_work_dir("plt-slot");
+    build_c_lib(
+        &dir,
+        "lazy_dep",
+        "int lazy_target(void) { return 11; }\n",
+        &[],
+        &["-O0", "-Wl,-z,lazy"],
+    );
+    let root = build_c_lib(
+        &dir,
+        "lazy_root",
+        r#"
+int lazy_target(void);
+int call_target(void) { return lazy_target(); }
+"#,
+        &["lazy_dep"],
+        &["-O0", "-Wl,-z,lazy"],
+    );
+    let offset = jump_slot_offset(&root, "lazy_target");
+
+    let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_LAZY).unwrap();
+    let slot = (lib.base() + offset) as *const usize;
+    let target = unsafe { lib.get::<()>("lazy_target").unwrap().into_raw() as usize };
+
+    // Before the first call the slot still points at the resolver path.
+    assert_ne!(unsafe { slot.read_volatile() }, target);
+
+    let call = unsafe { lib.get::<extern "C" fn() -> i32>("call_target").unwrap() };
+    assert_eq!(call(), 11);
+    assert_eq!(unsafe { slot.read_volatile() }, target);
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{env, process::Command, sync::Mutex};
+
+/// Set in the re-executed test binary, whose environment carries `DLOPEN_RS_DEBUG`.
+const CHILD_ENV: &str = "DLOPEN_RS_DEBUG_TEST_CHILD";
+
+static TARGETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl log::Log for Capture {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with("dlopen_rs::debug::")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            TARGETS.lock().unwrap().push(record.target().to_owned());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn only_requested_categories_are_traced() {
+    if env::var_os(CHILD_ENV).is_some() {
+        return;
+    }
+    if !has_command("cc") {
+        eprintln!("skipping DLOPEN_RS_DEBUG test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("categories");
+    let out = build_c_lib(&dir, "traced", "int traced_value = 1;\n", &[], &[]);
+
+    // The variable is read once per process, so the check runs in a fresh one.
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "traced_child", "--nocapture"])
+        .env(CHILD_ENV, &out)
+        .env("DLOPEN_RS_DEBUG", "libs")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn traced_child() {
+    let Some(path) = env::var_os(CHILD_ENV) else {
+        return;
+    };
+    log::set_logger(&Capture).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+
+    let _lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+
+    let targets = TARGETS.lock().unwrap();
+    assert!(
+        targets
+            .iter()
+            .any(|target| target == "dlopen_rs::debug::libs"),
+        "{targets:?}"
+    );
+    assert!(
+        targets
+            .iter()
+            .all(|target| target == "dlopen_rs::debug::libs"),
+        "{targets:?}"
+    );
+}
@@ -1,4 +1,5 @@
-use dlopen_rs::ElfLibrary;
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::process::Command;
 
 #[test]
 fn test_cache_lookup() {
@@ -11,3 +12,37 @@ fn test_cache_lookup() {
         res.err()
     );
 }
+
+#[test]
+fn ld_cache_paths_are_opened_verbatim() {
+    let Ok(output) = Command::new("ldconfig").arg("-p").output() else {
+        eprintln!("skipping ld.so.cache test because ldconfig is unavailable");
+        return;
+    };
+    // Multilib systems list a copy per ABI; only the one matching the target is loadable.
+    let arch_tag = match std::env::consts::ARCH {
+        "x86_64" => "(libc6,x86-64)",
+        "aarch64" => "(libc6,AArch64)",
+        "riscv64" | "loongarch64" => "(libc6,double-float)",
+        arch => {
+            eprintln!("skipping ld.so.cache test because the cache tag of {arch} is unknown");
+            return;
+        }
+    };
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let Some(cached) = listing
+        .lines()
+        .filter(|line| line.trim_start().starts_with("libz.so.1 ") && line.contains(arch_tag))
+        .find_map(|line| {
+            line.split_once(" => ")
+                .map(|(_, path)| path.trim().to_owned())
+        })
+    else {
+        eprintln!("skipping ld.so.cache test because libz.so.1 is not in the cache");
+        return;
+    };
+
+    let lib = ElfLibrary::dlopen("libz.so.1", OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(lib.name(), cached);
+    assert!(!lib.name().contains("//"));
+}
@@ -0,0 +1,525 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::fs;
+
+#[test]
+fn df_1_noopen_is_refused() {
+    if !has_command("cc") {
+        eprintln!("skipping DF_1_NOOPEN test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("df-1-noopen");
+    let path = build_c_lib(
+        &dir,
+        "noopen",
+        "int noopen_value(void) { return 7; }\n",
+        &[],
+        &["-Wl,-z,noopen"],
+    );
+    let path = path.to_str().unwrap();
+
+    assert!(matches!(
+        ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW),
+        Err(dlopen_rs::Error::FindLibError { .. })
+    ));
+    // The refused load must not leave anything registered behind.
+    assert!(ElfLibrary::dlopen(path, OpenFlags::RTLD_NOLOAD).is_err());
+}
+
+#[test]
+fn constructor_may_iterate_phdrs() {
+    if !has_command("cc") {
+        eprintln!("skipping constructor reentrancy test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("ctor-iterate-phdr");
+    let path = build_c_lib(
+        &dir,
+        "ctoriter",
+        r#"
+#define _GNU_SOURCE
+#include <link.h>
+
+static int seen;
+
+static int count(struct dl_phdr_info *info, size_t size, void *data) {
+    (void)info;
+    (void)size;
+    ++*(int *)data;
+    return 0;
+}
+
+__attribute__((constructor)) static void ctor(void) {
+    dl_iterate_phdr(count, &seen);
+}
+
+int ctor_seen(void) { return seen; }
+"#,
+        &[],
+        &[],
+    );
+
+    // Constructors run with the registry lock released; a reentrant call must not hang.
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+        let seen = unsafe { lib.get::<extern "C" fn() -> i32>("ctor_seen").unwrap() };
+        tx.send(seen()).unwrap();
+    });
+    let seen = rx
+        .recv_timeout(std::time::Duration::from_secs(30))
+        .expect("dlopen deadlocked while the constructor iterated program headers");
+    assert!(seen > 0);
+}
+
+#[test]
+fn failed_load_unmaps_dependencies() {
+    if !has_command("cc") {
+        eprintln!("skipping rollback test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("rollback");
+    build_c_lib(
+        &dir,
+        "rbfail",
+        r#"
+int rb_missing_symbol(void);
+int rb_fail_value(void) { return rb_missing_symbol(); }
+"#,
+        &[],
+        &[],
+    );
+    build_c_lib(
+        &dir,
+        "rbmid",
+        r#"
+#include <stdlib.h>
+int rb_fail_value(void);
+__attribute__((constructor)) static void ctor(void) { setenv("DLOPEN_RS_RB_CTOR", "1", 1); }
+int rb_mid_value(void) { return rb_fail_value(); }
+"#,
+        &["rbfail"],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "rbroot",
+        r#"
+int rb_mid_value(void);
+int rb_root_value(void) { return rb_mid_value(); }
+"#,
+        &["rbmid"],
+        &[],
+    );
+
+    assert!(ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).is_err());
+
+    // No constructor runs before the whole group is relocated.
+    assert!(std::env::var_os("DLOPEN_RS_RB_CTOR").is_none());
+    // Every library mapped by the failed load is gone again.
+    let dir = fs::canonicalize(&dir).unwrap();
+    let maps = fs::read_to_string("/proc/self/maps").unwrap();
+    assert!(
+        !maps.contains(dir.to_str().unwrap()),
+        "residual mappings after a failed load:\n{maps}"
+    );
+    for name in ["librbroot.so", "librbmid.so", "librbfail.so"] {
+        assert!(ElfLibrary::dlopen(name, OpenFlags::RTLD_NOLOAD).is_err());
+    }
+}
+
+#[test]
+fn mutual_dependencies_load_and_unload() {
+    if !has_command("cc") {
+        eprintln!("skipping dependency cycle test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("cycle");
+    let source_b = r#"
+int cycle_a_value(void);
+int cycle_b_value(void) { return 20; }
+int cycle_b_calls_a(void) { return cycle_a_value() + 2; }
+"#;
+    // Build the first half without its DT_NEEDED entry, then again once the other half exists.
+    build_c_lib(&dir, "cycle_b", source_b, &[], &[]);
+    let root = build_c_lib(
+        &dir,
+        "cycle_a",
+        r#"
+int cycle_b_value(void);
+int cycle_a_value(void) { return 10; }
+int cycle_a_calls_b(void) { return cycle_b_value() + 1; }
+"#,
+        &["cycle_b"],
+        &["-Wl,--no-as-needed"],
+    );
+    let dep = build_c_lib(
+        &dir,
+        "cycle_b",
+        source_b,
+        &["cycle_a"],
+        &["-Wl,--no-as-needed"],
+    );
+
+    {
+        let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+        let a_calls_b = unsafe {
+            lib.get::<extern "C" fn() -> i32>("cycle_a_calls_b")
+                .unwrap()
+        };
+        let b_calls_a = unsafe {
+            lib.get::<extern "C" fn() -> i32>("cycle_b_calls_a")
+                .unwrap()
+        };
+        assert_eq!(a_calls_b(), 21);
+        assert_eq!(b_calls_a(), 12);
+    }
+
+    // Each half only keeps the other alive, so dropping the handle releases both.
+    for path in [&root, &dep] {
+        assert!(
+            ElfLibrary::dlopen(
+                path.to_str().unwrap(),
+                OpenFlags::RTLD_NOW | OpenFlags::RTLD_NOLOAD
+            )
+            .is_err()
+        );
+    }
+}
+
+#[test]
+fn deferred_constructors_run_on_request() {
+    if !has_command("cc") {
+        eprintln!("skipping deferred constructor test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("no-init");
+    build_c_lib(
+        &dir,
+        "noinit_dep",
+        r#"
+int noinit_dep_ready;
+__attribute__((constructor)) static void noinit_dep_init(void) { noinit_dep_ready = 1; }
+"#,
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "noinit_root",
+        r#"
+extern int noinit_dep_ready;
+int noinit_ready;
+int noinit_saw_dep;
+__attribute__((constructor)) static void noinit_init(void) {
+    noinit_ready = 1;
+    noinit_saw_dep = noinit_dep_ready;
+}
+"#,
+        &["noinit_dep"],
+        &["-Wl,--no-as-needed"],
+    );
+
+    let lib = ElfLibrary::dlopen_no_init(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let ready = unsafe { *lib.get::<*mut i32>("noinit_ready").unwrap() };
+    let saw_dep = unsafe { *lib.get::<*const i32>("noinit_saw_dep").unwrap() };
+    let dep_ready = unsafe { *lib.get::<*const i32>("noinit_dep_ready").unwrap() };
+    unsafe {
+        assert_eq!(*ready, 0);
+        assert_eq!(*dep_ready, 0);
+        lib.run_constructors();
+        assert_eq!(*ready, 1);
+        assert_eq!(*dep_ready, 1);
+        // Dependencies are initialized before the libraries that need them.
+        assert_eq!(*saw_dep, 1);
+
+        *ready = 7;
+        lib.run_constructors();
+        assert_eq!(*ready, 7);
+    }
+}
+
+static UNINIT_FINI_RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+extern "C" fn count_uninit_fini_run() {
+    UNINIT_FINI_RUNS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+#[test]
+fn deferred_constructors_gate_destructors_and_plain_dlopen() {
+    if !has_command("cc") {
+        eprintln!("skipping deferred constructor test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("no-init-fini");
+    let path = build_c_lib(
+        &dir,
+        "noinit_fini",
+        r#"
+static void (*hook)(void);
+int noinit_fini_ready;
+void set_noinit_fini_hook(void (*h)(void)) { hook = h; }
+__attribute__((constructor)) static void noinit_fini_init(void) { noinit_fini_ready = 1; }
+__attribute__((destructor)) static void noinit_fini_fini(void) { if (hook) hook(); }
+"#,
+        &[],
+        &[],
+    );
+
+    // Unloading a library whose constructors never ran skips its destructors.
+    let lib = ElfLibrary::dlopen_no_init(&path, OpenFlags::RTLD_NOW).unwrap();
+    {
+        let set_hook = unsafe {
+            lib.get::<extern "C" fn(extern "C" fn())>("set_noinit_fini_hook")
+                .unwrap()
+        };
+        set_hook(count_uninit_fini_run);
+    }
+    assert!(matches!(
+        lib.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    assert_eq!(
+        UNINIT_FINI_RUNS.load(std::sync::atomic::Ordering::SeqCst),
+        0
+    );
+
+    // A plain `dlopen` of a deferred library runs its constructors before returning it.
+    let deferred = ElfLibrary::dlopen_no_init(&path, OpenFlags::RTLD_NOW).unwrap();
+    let ready = unsafe { *deferred.get::<*const i32>("noinit_fini_ready").unwrap() };
+    assert_eq!(unsafe { *ready }, 0);
+    let plain = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(unsafe { *ready }, 1);
+    {
+        let set_hook = unsafe {
+            plain
+                .get::<extern "C" fn(extern "C" fn())>("set_noinit_fini_hook")
+                .unwrap()
+        };
+        set_hook(count_uninit_fini_run);
+    }
+    drop(deferred);
+    drop(plain);
+    assert_eq!(
+        UNINIT_FINI_RUNS.load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+}
+
+static DESTRUCTOR_RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+extern "C" fn count_destructor_run() {
+    DESTRUCTOR_RUNS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+#[test]
+fn destructors_run_once_when_invoked_early() {
+    if !has_command("cc") {
+        eprintln!("skipping early destructor test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("early-fini");
+    let path = build_c_lib(
+        &dir,
+        "earlyfini",
+        r#"
+static void (*hook)(void);
+void set_fini_hook(void (*h)(void)) { hook = h; }
+__attribute__((destructor)) static void early_fini(void) { if (hook) hook(); }
+"#,
+        &[],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    {
+        let set_hook = unsafe {
+            lib.get::<extern "C" fn(extern "C" fn())>("set_fini_hook")
+                .unwrap()
+        };
+        set_hook(count_destructor_run);
+    }
+
+    lib.run_destructors();
+    assert_eq!(DESTRUCTOR_RUNS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    lib.run_destructors();
+    assert_eq!(DESTRUCTOR_RUNS.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(
+        lib.close(),
+        dlopen_rs::UnloadOutcome::Unloaded {
+            ran_atexit_handlers: false
+        }
+    );
+    assert_eq!(DESTRUCTOR_RUNS.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // A reload, likely at the same address, runs its destructors on unload again.
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    {
+        let set_hook = unsafe {
+            lib.get::<extern "C" fn(extern "C" fn())>("set_fini_hook")
+                .unwrap()
+        };
+        set_hook(count_destructor_run);
+    }
+    drop(lib);
+    assert_eq!(DESTRUCTOR_RUNS.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+static ATEXIT_RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+extern "C" fn count_atexit_run() {
+    ATEXIT_RUNS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+#[test]
+fn close_reports_that_atexit_handlers_ran() {
+    if !has_command("cc") {
+        eprintln!("skipping atexit destructor test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("close-atexit");
+    let path = build_c_lib(
+        &dir,
+        "closeatexit",
+        r#"
+extern void *__dso_handle;
+int __cxa_atexit(void (*func)(void *), void *arg, void *dso_handle);
+static void run_hook(void *hook) { ((void (*)(void))hook)(); }
+void register_exit_hook(void (*hook)(void)) { __cxa_atexit(run_hook, (void *)hook, &__dso_handle); }
+"#,
+        &[],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    {
+        let register = unsafe {
+            lib.get::<extern "C" fn(extern "C" fn())>("register_exit_hook")
+                .unwrap()
+        };
+        register(count_atexit_run);
+    }
+    let other = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+
+    assert_eq!(other.close(), dlopen_rs::UnloadOutcome::StillReferenced);
+    assert_eq!(ATEXIT_RUNS.load(std::sync::atomic::Ordering::SeqCst), 0);
+    assert_eq!(
+        lib.close(),
+        dlopen_rs::UnloadOutcome::Unloaded {
+            ran_atexit_handlers: true
+        }
+    );
+    assert_eq!(ATEXIT_RUNS.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn leaked_library_outlives_every_handle() {
+    if !has_command("cc") {
+        eprintln!("skipping leak test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("leak");
+    let path = build_c_lib(
+        &dir,
+        "leaked",
+        "int leaked_value(void) { return 17; }\n",
+        &[],
+        &[],
+    );
+
+    let other = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    let leaked = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW)
+        .unwrap()
+        .leak();
+    assert_eq!(other.close(), dlopen_rs::UnloadOutcome::NoDelete);
+
+    let leaked_value = unsafe {
+        leaked
+            .get::<extern "C" fn() -> i32>("leaked_value")
+            .unwrap()
+    };
+    assert_eq!(leaked_value(), 17);
+    assert_eq!(
+        ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOLOAD)
+            .unwrap()
+            .base(),
+        leaked.base()
+    );
+}
+
+#[test]
+fn nodelete_root_does_not_pin_dependencies_by_flag() {
+    if !has_command("cc") {
+        eprintln!("skipping RTLD_NODELETE propagation test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("nodelete-deps");
+    let dep = build_c_lib(
+        &dir,
+        "nodelete_dep",
+        "int nodelete_dep(void) { return 6; }\n",
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "nodelete_root",
+        "int nodelete_dep(void);\nint nodelete_root(void) { return nodelete_dep(); }\n",
+        &["nodelete_dep"],
+        &[],
+    );
+
+    let root = ElfLibrary::dlopen(&root, OpenFlags::RTLD_NOW | OpenFlags::RTLD_NODELETE).unwrap();
+    let dep_handle = ElfLibrary::dlopen(&dep, OpenFlags::RTLD_NOLOAD).unwrap();
+    assert!(root.flags().contains(OpenFlags::RTLD_NODELETE));
+    assert!(!dep_handle.flags().contains(OpenFlags::RTLD_NODELETE));
+
+    // The dependency is not pinned itself, but the root still needs it.
+    assert_eq!(
+        dep_handle.close(),
+        dlopen_rs::UnloadOutcome::StillReferenced
+    );
+    assert_eq!(root.close(), dlopen_rs::UnloadOutcome::NoDelete);
+    let dep_handle = ElfLibrary::dlopen(&dep, OpenFlags::RTLD_NOLOAD).unwrap();
+    let nodelete_dep = unsafe {
+        dep_handle
+            .get::<extern "C" fn() -> i32>("nodelete_dep")
+            .unwrap()
+    };
+    assert_eq!(nodelete_dep(), 6);
+}
+
+#[test]
+fn link_map_stats_count_loads_and_unloads() {
+    if !has_command("cc") {
+        eprintln!("skipping link map statistics test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("link-map-stats");
+    let path = build_c_lib(&dir, "churn", "int churn(void) { return 0; }\n", &[], &[]);
+
+    // Other tests load and unload concurrently, so only growth is checked.
+    let (adds, subs) = ElfLibrary::link_map_stats();
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    let (loaded_adds, _) = ElfLibrary::link_map_stats();
+    assert!(loaded_adds > adds);
+    assert!(matches!(
+        lib.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    let (_, unloaded_subs) = ElfLibrary::link_map_stats();
+    assert!(unloaded_subs > subs);
+}
@@ -0,0 +1,53 @@
+//! Kept apart from the other tests because the load root is process-wide.
+
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, Error, OpenFlags};
+use std::fs;
+
+#[test]
+fn paths_outside_the_load_root_are_refused() {
+    if !has_command("cc") {
+        eprintln!("skipping load root test because cc is unavailable");
+        return;
+    }
+
+    let base = test_work_dir("outside-paths");
+    let root = base.join("plugins");
+    let outside = base.join("outside");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    build_c_lib(
+        &root,
+        "inroot",
+        "int in_root(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+    let escaped = build_c_lib(
+        &outside,
+        "escaped",
+        "int escaped(void) { return 2; }\n",
+        &[],
+        &[],
+    );
+    let link = root.join("liblinked.so");
+    let _ = fs::remove_file(&link);
+    std::os::unix::fs::symlink(fs::canonicalize(&escaped).unwrap(), &link).unwrap();
+
+    ElfLibrary::set_load_root(Some(&root)).unwrap();
+    let inside = ElfLibrary::dlopen(root.join("libinroot.so"), OpenFlags::RTLD_NOW);
+    let dotdot = ElfLibrary::dlopen(root.join("../outside/libescaped.so"), OpenFlags::RTLD_NOW);
+    let symlink = ElfLibrary::dlopen(&link, OpenFlags::RTLD_NOW);
+    ElfLibrary::set_load_root(None).unwrap();
+
+    let inside = inside.unwrap();
+    let in_root = unsafe { inside.get::<extern "C" fn() -> i32>("in_root").unwrap() };
+    assert_eq!(in_root(), 1);
+    assert!(matches!(dotdot, Err(Error::InvalidPath)), "{dotdot:?}");
+    assert!(matches!(symlink, Err(Error::InvalidPath)), "{symlink:?}");
+
+    // Without a root the same library loads.
+    assert!(ElfLibrary::dlopen(&link, OpenFlags::RTLD_NOW).is_ok());
+}
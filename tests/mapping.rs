@@ -0,0 +1,308 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::fs;
+
+#[test]
+fn segment_queries() {
+    if !has_command("cc") {
+        eprintln!("skipping segment query test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("segments");
+    let exec = build_c_lib(
+        &dir,
+        "execstack",
+        "__thread int tls_value = 1;\nint exec_value(void) { return tls_value; }\n",
+        &[],
+        &["-Wl,-z,execstack"],
+    );
+    let noexec = build_c_lib(
+        &dir,
+        "noexecstack",
+        "int noexec_value(void) { return 2; }\n",
+        &[],
+        &["-Wl,-z,noexecstack"],
+    );
+
+    let exec = ElfLibrary::dlopen(exec.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    assert!(exec.executable_stack());
+    assert!(exec.tls_segment().is_some());
+
+    let noexec = ElfLibrary::dlopen(noexec.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    assert!(!noexec.executable_stack());
+    assert!(noexec.tls_segment().is_none());
+}
+
+#[test]
+fn load_bias_with_nonzero_first_segment() {
+    if !has_command("cc") {
+        eprintln!("skipping load bias test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("load-bias");
+    let path = build_c_lib(
+        &dir,
+        "biased",
+        "int biased_value(void) { return 5; }\n",
+        &[],
+        &["-Wl,-Ttext-segment=0x200000"],
+    );
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let lowest_vaddr = lib
+        .phdrs()
+        .unwrap()
+        .iter()
+        .filter(|phdr| phdr.p_memsz() != 0)
+        .map(|phdr| phdr.p_vaddr())
+        .min();
+    assert_eq!(lowest_vaddr, Some(0x200000));
+
+    let info = lib.symbol_info("biased_value").unwrap();
+    assert_eq!(lib.load_bias() + info.value, info.address);
+    let biased_value = unsafe { lib.get::<extern "C" fn() -> i32>("biased_value").unwrap() };
+    assert_eq!(biased_value as usize, info.address);
+    assert_eq!(biased_value(), 5);
+}
+
+/// (vaddr, offset, filesz, memsz) of every PT_LOAD of a 64-bit ELF file.
+fn load_segments(bytes: &[u8]) -> Vec<(usize, usize, usize, usize)> {
+    let read_u16 = |at: usize| u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap()) as usize;
+    let read_u64 = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize;
+    let (phoff, phentsize, phnum) = (read_u64(32), read_u16(54), read_u16(56));
+    (0..phnum)
+        .map(|i| phoff + i * phentsize)
+        .filter(|&ph| u32::from_le_bytes(bytes[ph..ph + 4].try_into().unwrap()) == 1)
+        .map(|ph| {
+            (
+                read_u64(ph + 16),
+                read_u64(ph + 8),
+                read_u64(ph + 32),
+                read_u64(ph + 40),
+            )
+        })
+        .collect()
+}
+
+/// The length of a mapping that holds every PT_LOAD of the file, rounded up to 64 KiB.
+fn mapped_image_len(bytes: &[u8]) -> usize {
+    load_segments(bytes)
+        .iter()
+        .map(|&(vaddr, _, _, memsz)| vaddr + memsz)
+        .max()
+        .unwrap()
+        .next_multiple_of(0x10000)
+}
+
+fn map_anonymous(len: usize) -> usize {
+    let base = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(base, libc::MAP_FAILED);
+    base as usize
+}
+
+/// Lays out the file contents of every PT_LOAD at `base`, as a loader would.
+unsafe fn copy_image(bytes: &[u8], base: usize) {
+    for (vaddr, offset, filesz, _) in load_segments(bytes) {
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                bytes[offset..offset + filesz].as_ptr(),
+                (base + vaddr) as *mut u8,
+                filesz,
+            )
+        };
+    }
+}
+
+#[test]
+fn from_mapped_links_caller_mapping() {
+    if !has_command("cc") {
+        eprintln!("skipping pre-mapped image test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("from-mapped");
+    let path = build_c_lib(
+        &dir,
+        "premapped",
+        "int premapped_value = 40;\nint premapped_get(void) { return premapped_value + 2; }\n",
+        &[],
+        &[],
+    );
+    let bytes = fs::read(&path).unwrap();
+    let len = mapped_image_len(&bytes);
+    let base = map_anonymous(len);
+    unsafe { copy_image(&bytes, base) };
+
+    let lib = unsafe { ElfLibrary::from_mapped(base, len, "libpremapped.so", OpenFlags::RTLD_NOW) }
+        .unwrap();
+    assert_eq!(lib.base(), base);
+    let get = unsafe { lib.get::<extern "C" fn() -> i32>("premapped_get").unwrap() };
+    assert_eq!(get(), 42);
+}
+
+#[test]
+fn dladdr_prefers_latest_of_overlapping_mappings() {
+    if !has_command("cc") {
+        eprintln!("skipping overlapping mapping test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("overlapping-mappings");
+    let outer = fs::read(build_c_lib(
+        &dir,
+        "overlapouter",
+        "int overlap_outer(void) { return 1; }\n",
+        &[],
+        &[],
+    ))
+    .unwrap();
+    let inner = fs::read(build_c_lib(
+        &dir,
+        "overlapinner",
+        "int overlap_inner(void) { return 2; }\n",
+        &[],
+        &[],
+    ))
+    .unwrap();
+
+    // The outer library claims the whole region, the inner one sits in its tail.
+    let (outer_len, inner_len) = (mapped_image_len(&outer), mapped_image_len(&inner));
+    let base = map_anonymous(outer_len + inner_len);
+    let inner_base = base + outer_len;
+    unsafe {
+        copy_image(&outer, base);
+        copy_image(&inner, inner_base);
+    }
+    let _outer = unsafe {
+        ElfLibrary::from_mapped(
+            base,
+            outer_len + inner_len,
+            "liboverlapouter.so",
+            OpenFlags::RTLD_NOW,
+        )
+    }
+    .unwrap();
+    let inner = unsafe {
+        ElfLibrary::from_mapped(
+            inner_base,
+            inner_len,
+            "liboverlapinner.so",
+            OpenFlags::RTLD_NOW,
+        )
+    }
+    .unwrap();
+
+    let func = unsafe {
+        inner
+            .get::<extern "C" fn() -> i32>("overlap_inner")
+            .unwrap()
+    };
+    assert_eq!(func(), 2);
+    let info = ElfLibrary::dladdr(func.into_raw() as usize).unwrap();
+    assert_eq!(info.dylib().name(), "liboverlapinner.so");
+}
+
+#[test]
+fn iterate_phdr_callback_may_dlopen() {
+    if !has_command("cc") {
+        eprintln!("skipping phdr callback reentrancy test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("iterate-phdr-dlopen");
+    let path = build_c_lib(
+        &dir,
+        "iterdlopen",
+        "int iter_dlopen_value(void) { return 8; }\n",
+        &[],
+        &[],
+    );
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut opened = None;
+        ElfLibrary::dl_iterate_phdr(|_| {
+            if opened.is_none() {
+                opened = Some(ElfLibrary::dlopen(
+                    path.to_str().unwrap(),
+                    OpenFlags::RTLD_NOW,
+                )?);
+            }
+            Ok(())
+        })
+        .unwrap();
+        let lib = opened.unwrap();
+        let value = unsafe {
+            lib.get::<extern "C" fn() -> i32>("iter_dlopen_value")
+                .unwrap()
+        };
+        tx.send(value()).unwrap();
+    });
+    let value = rx
+        .recv_timeout(std::time::Duration::from_secs(30))
+        .expect("dlopen from a dl_iterate_phdr callback deadlocked");
+    assert_eq!(value, 8);
+}
+
+#[test]
+fn build_id_from_note_segment() {
+    if !has_command("cc") {
+        eprintln!("skipping build-id test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("build-id");
+    let source = "int build_id_value(void) { return 3; }\n";
+    let with_id = build_c_lib(&dir, "withid", source, &[], &["-Wl,--build-id=sha1"]);
+    let without_id = build_c_lib(&dir, "withoutid", source, &[], &["-Wl,--build-id=none"]);
+
+    let lib = ElfLibrary::dlopen(with_id.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let id = lib.build_id().expect("library was linked with a build-id");
+    assert_eq!(id.len(), 20);
+    assert!(id.iter().any(|&byte| byte != 0));
+
+    let lib = ElfLibrary::dlopen(without_id.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(lib.build_id(), None);
+}
+
+#[test]
+fn debug_output_names_library_and_dependencies() {
+    if !has_command("cc") {
+        eprintln!("skipping debug output test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("debug-output");
+    build_c_lib(
+        &dir,
+        "debug_dep",
+        "int debug_dep(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+    let path = build_c_lib(
+        &dir,
+        "debug_root",
+        "int debug_dep(void);\nint debug_root(void) { return debug_dep(); }\n",
+        &["debug_dep"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    let debug = format!("{lib:?}");
+    assert!(debug.contains("libdebug_root.so"), "{debug}");
+    assert!(debug.contains("libdebug_dep.so"), "{debug}");
+    assert!(debug.contains(&format!("{:#x}", lib.base())), "{debug}");
+}
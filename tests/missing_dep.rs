@@ -0,0 +1,47 @@
+//! Kept apart from the other tests because the missing dependency policy is process-wide.
+
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, MissingDepPolicy, OpenFlags};
+use std::fs;
+
+#[test]
+fn lenient_policy_skips_missing_dependency() {
+    if !has_command("cc") {
+        eprintln!("skipping missing dependency test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("lenient");
+    let dep = build_c_lib(
+        &dir,
+        "gone",
+        "int gone_value(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "needs_gone",
+        "#include <stdlib.h>\nint needs_gone_abs(int v) { return abs(v); }\n",
+        &["gone"],
+        &["-Wl,--no-as-needed"],
+    );
+    // The dependency is declared but absent; `abs` comes from the global scope.
+    fs::remove_file(&dep).unwrap();
+
+    let path = root.to_str().unwrap();
+    let err = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap_err();
+    assert!(err.to_string().contains("libgone.so"), "{err}");
+
+    ElfLibrary::set_missing_dep_policy(MissingDepPolicy::Lenient);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW);
+    ElfLibrary::set_missing_dep_policy(MissingDepPolicy::Strict);
+    let lib = lib.unwrap();
+    let needs_gone_abs = unsafe {
+        lib.get::<extern "C" fn(i32) -> i32>("needs_gone_abs")
+            .unwrap()
+    };
+    assert_eq!(needs_gone_abs(-4), 4);
+}
@@ -0,0 +1,172 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{fs, process::Command};
+
+#[test]
+fn load_stats_counts_relocations() {
+    if !has_command("cc") {
+        eprintln!("skipping load statistics test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("load-stats");
+    let source = r#"
+#include <stdlib.h>
+static int target;
+int *stats_ptr = &target;
+int stats_abs(int v) { return abs(v); }
+"#;
+    let now = build_c_lib(&dir, "stats_now", source, &[], &["-Wl,-z,lazy"]);
+    let lazy = build_c_lib(&dir, "stats_lazy", source, &[], &["-Wl,-z,lazy"]);
+
+    let lib = ElfLibrary::dlopen(now.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let stats = lib.load_stats();
+    assert_eq!(stats.symbols, lib.symbol_count());
+    assert!(stats.symbols > 0);
+    assert!(stats.relocations > 0);
+    assert_eq!(stats.deferred, 0);
+
+    let lib = ElfLibrary::dlopen(lazy.to_str().unwrap(), OpenFlags::RTLD_LAZY).unwrap();
+    let lazy_stats = lib.load_stats();
+    // `abs` goes through the PLT, which lazy binding leaves for later.
+    assert!(lazy_stats.deferred > 0);
+    assert_eq!(
+        lazy_stats.relocations + lazy_stats.deferred,
+        stats.relocations
+    );
+    let stats_abs = unsafe { lib.get::<extern "C" fn(i32) -> i32>("stats_abs").unwrap() };
+    assert_eq!(stats_abs(-3), 3);
+}
+
+#[test]
+fn packed_relative_relocations() {
+    if !has_command("cc") {
+        eprintln!("skipping RELR test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("relr");
+    let src = dir.join("relr.c");
+    let out = dir.join("librelr.so");
+    // Enough adjacent pointers that the linker emits bitmap entries, not just addresses.
+    fs::write(
+        &src,
+        r#"
+static int relr_values[40];
+int *relr_table[40] = {
+    &relr_values[0], &relr_values[1], &relr_values[2], &relr_values[3], &relr_values[4],
+    &relr_values[5], &relr_values[6], &relr_values[7], &relr_values[8], &relr_values[9],
+    &relr_values[10], &relr_values[11], &relr_values[12], &relr_values[13], &relr_values[14],
+    &relr_values[15], &relr_values[16], &relr_values[17], &relr_values[18], &relr_values[19],
+    &relr_values[20], &relr_values[21], &relr_values[22], &relr_values[23], &relr_values[24],
+    &relr_values[25], &relr_values[26], &relr_values[27], &relr_values[28], &relr_values[29],
+    &relr_values[30], &relr_values[31], &relr_values[32], &relr_values[33], &relr_values[34],
+    &relr_values[35], &relr_values[36], &relr_values[37], &relr_values[38], &relr_values[39],
+};
+int relr_sum(void) {
+    int sum = 0;
+    for (int i = 0; i < 40; i++) {
+        *relr_table[i] = i;
+        sum += *relr_table[i];
+    }
+    return sum;
+}
+"#,
+    )
+    .unwrap();
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-Wl,-z,pack-relative-relocs"])
+        .arg(&src)
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .expect("failed to compile test library");
+    if !status.success() {
+        eprintln!("skipping RELR test because the linker lacks -z pack-relative-relocs");
+        return;
+    }
+
+    let lib = ElfLibrary::dlopen(out.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let table = unsafe { *lib.get::<*const *const i32>("relr_table").unwrap() };
+    for i in 0..40 {
+        let ptr = unsafe { *table.add(i) } as usize;
+        assert!(
+            (lib.base()..lib.base() + lib.mapped_len()).contains(&ptr),
+            "slot {i} was not relocated: {ptr:#x}"
+        );
+    }
+    let relr_sum = unsafe { lib.get::<extern "C" fn() -> i32>("relr_sum").unwrap() };
+    assert_eq!(relr_sum(), (0..40).sum::<i32>());
+}
+
+#[test]
+fn glibc_abi_version_requirement_is_satisfied() {
+    if !has_command("cc") {
+        eprintln!("skipping GLIBC_ABI_DT_RELR test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("glibc-abi");
+    let src = dir.join("abirelr.c");
+    let out = dir.join("libabirelr.so");
+    fs::write(
+        &src,
+        "#include <string.h>\nstatic int abi_values[2];\nint *abi_table[2] = { &abi_values[0], &abi_values[1] };\nsize_t abi_len(const char *s) { return strlen(s) + (abi_table[1] - abi_table[0]); }\n",
+    )
+    .unwrap();
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-Wl,-z,pack-relative-relocs"])
+        .arg(&src)
+        .arg("-o")
+        .arg(&out)
+        .status()
+        .expect("failed to compile test library");
+    if !status.success() {
+        eprintln!(
+            "skipping GLIBC_ABI_DT_RELR test because the linker lacks -z pack-relative-relocs"
+        );
+        return;
+    }
+    // Only glibc 2.36 and later define the pseudo-version the linker records.
+    let bytes = fs::read(&out).unwrap();
+    if !bytes
+        .windows(b"GLIBC_ABI_DT_RELR".len())
+        .any(|window| window == b"GLIBC_ABI_DT_RELR")
+    {
+        eprintln!("skipping GLIBC_ABI_DT_RELR test because libc does not define the version");
+        return;
+    }
+
+    let lib = ElfLibrary::dlopen(out.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let abi_len = unsafe {
+        lib.get::<extern "C" fn(*const std::ffi::c_char) -> usize>("abi_len")
+            .unwrap()
+    };
+    assert_eq!(abi_len(c"four".as_ptr()), 5);
+}
+
+#[test]
+fn eager_load_reports_every_unresolved_symbol() {
+    if !has_command("cc") {
+        eprintln!("skipping unresolved symbol report test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("unresolved-report");
+    let path = build_c_lib(
+        &dir,
+        "unresolved",
+        "int missing_one(void);\nint missing_two(void);\nextern int missing_three;\nint unresolved_use(void) { return missing_one() + missing_two() + missing_three; }\n",
+        &[],
+        &[],
+    );
+
+    let err = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap_err();
+    let msg = err.to_string();
+    for name in ["missing_one", "missing_two", "missing_three"] {
+        assert!(msg.contains(name), "{msg}");
+    }
+    assert!(!msg.contains("unresolved_use"), "{msg}");
+}
@@ -1,15 +1,12 @@
 #![cfg(target_arch = "x86_64")]
 
+mod common;
+
+use common::{has_command, target_dir, test_work_dir};
 use std::{fs, path::PathBuf, process::Command, sync::OnceLock};
 
 const RTLD_TARGET: &str = "x86_64-unknown-linux-none";
 
-fn target_dir() -> PathBuf {
-    option_env!("CARGO_TARGET_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("target"))
-}
-
 fn rtld_path() -> PathBuf {
     target_dir()
         .join(RTLD_TARGET)
@@ -24,14 +21,6 @@ fn rtld_interp_path() -> PathBuf {
         .join("ld-linux-x86-64.so.2")
 }
 
-fn has_command(program: &str) -> bool {
-    Command::new(program)
-        .arg("--version")
-        .output()
-        .map(|out| out.status.success())
-        .unwrap_or(false)
-}
-
 fn command_output(program: &str, args: &[&str]) -> String {
     let output = Command::new(program)
         .args(args)
@@ -91,12 +80,6 @@ fn build_rtld() -> Option<PathBuf> {
     Some(rtld_path())
 }
 
-fn test_work_dir(name: &str) -> PathBuf {
-    let dir = target_dir().join("rtld-tests").join(name);
-    fs::create_dir_all(&dir).unwrap();
-    dir
-}
-
 #[test]
 fn rtld_artifact_has_interpreter_shape() {
     let Some(path) = build_rtld() else {
@@ -0,0 +1,69 @@
+//! Kept apart from the other tests because sibling lookup is process-wide.
+
+mod common;
+
+use common::{has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{fs, process::Command};
+
+fn cc(args: &[&str]) {
+    let status = Command::new("cc")
+        .args(args)
+        .status()
+        .expect("failed to compile test library");
+    assert!(status.success(), "cc {args:?} failed");
+}
+
+#[test]
+fn dependency_found_next_to_its_owner() {
+    if !has_command("cc") {
+        eprintln!("skipping sibling lookup test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("owner-dir");
+    let dir_str = dir.to_str().unwrap();
+    let helper_src = dir.join("sibhelper.c");
+    let helper = dir.join("libsibhelper.so");
+    let plugin_src = dir.join("sibplugin.c");
+    let plugin = dir.join("libsibplugin.so");
+    fs::write(&helper_src, "int sib_helper(void) { return 30; }\n").unwrap();
+    fs::write(
+        &plugin_src,
+        "int sib_helper(void);\nint sib_plugin(void) { return sib_helper() + 1; }\n",
+    )
+    .unwrap();
+    cc(&[
+        "-shared",
+        "-fPIC",
+        helper_src.to_str().unwrap(),
+        "-o",
+        helper.to_str().unwrap(),
+    ]);
+    // No rpath: the helper is only reachable as a sibling.
+    cc(&[
+        "-shared",
+        "-fPIC",
+        plugin_src.to_str().unwrap(),
+        "-o",
+        plugin.to_str().unwrap(),
+        &format!("-L{dir_str}"),
+        "-lsibhelper",
+    ]);
+
+    let path = plugin.to_str().unwrap();
+    let err = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap_err();
+    assert!(err.to_string().contains("libsibhelper.so"), "{err}");
+
+    ElfLibrary::set_sibling_lookup(true);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW);
+    ElfLibrary::set_sibling_lookup(false);
+    let lib = lib.unwrap();
+    let sib_plugin = unsafe { lib.get::<extern "C" fn() -> i32>("sib_plugin").unwrap() };
+    assert_eq!(sib_plugin(), 31);
+    assert!(
+        lib.resolved_dependencies()
+            .iter()
+            .any(|&(needed, path)| needed == "libsibhelper.so" && path.starts_with(dir_str))
+    );
+}
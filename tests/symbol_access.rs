@@ -0,0 +1,230 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::fs;
+
+#[test]
+fn static_accessors_read_and_write() {
+    if !has_command("cc") {
+        eprintln!("skipping static accessor test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("statics");
+    let path = build_c_lib(
+        &dir,
+        "statics",
+        "int static_counter = 5;\nint static_counter_get(void) { return static_counter; }\n",
+        &[],
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let get = unsafe {
+        lib.get::<extern "C" fn() -> i32>("static_counter_get")
+            .unwrap()
+    };
+
+    unsafe {
+        assert_eq!(*lib.get_static::<i32>("static_counter").unwrap(), 5);
+        *lib.get_static_mut::<i32>("static_counter").unwrap() = 9;
+        assert_eq!(*lib.get_static::<i32>("static_counter").unwrap(), 9);
+        assert!(lib.get_static::<u64>("static_counter").is_err());
+        assert!(lib.get_static::<i32>("no_such_static").is_err());
+    }
+    assert_eq!(get(), 9);
+}
+
+#[cfg(feature = "version")]
+#[test]
+fn unversioned_lookup_skips_hidden_version() {
+    if !has_command("cc") {
+        eprintln!("skipping hidden version test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("hidden-version");
+    let script = dir.join("versions.map");
+    fs::write(&script, "V1 { local: *; };\nV2 { } V1;\n").unwrap();
+    let path = build_c_lib(
+        &dir,
+        "hiddenver",
+        r#"
+int foo_v1(void) { return 1; }
+int foo_v2(void) { return 2; }
+__asm__(".symver foo_v1, foo@V1");
+__asm__(".symver foo_v2, foo@@V2");
+"#,
+        &[],
+        &[&format!("-Wl,--version-script,{}", script.display())],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let (default, old, newest) = unsafe {
+        (
+            lib.get::<extern "C" fn() -> i32>("foo").unwrap(),
+            lib.get_version::<extern "C" fn() -> i32>("foo", "V1")
+                .unwrap(),
+            lib.get_version::<extern "C" fn() -> i32>("foo", "V2")
+                .unwrap(),
+        )
+    };
+    assert_eq!(default(), 2);
+    assert_eq!(old(), 1);
+    assert_eq!(newest(), 2);
+    assert_ne!(default.into_raw(), old.into_raw());
+}
+
+#[cfg(feature = "version")]
+#[test]
+fn searchlist_lookups_pick_the_default_version() {
+    if !has_command("cc") {
+        eprintln!("skipping default version test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("default-version-lookups");
+    let script = dir.join("versions.map");
+    fs::write(&script, "V1 { local: *; };\nV2 { } V1;\n").unwrap();
+    let path = build_c_lib(
+        &dir,
+        "defaultver",
+        r#"
+int foo_v1(void) { return 1; }
+int foo_v2(void) { return 2; }
+int bar_v1 = 1;
+long long bar_v2 = 2;
+__asm__(".symver foo_v1, foo@V1");
+__asm__(".symver foo_v2, foo@@V2");
+__asm__(".symver bar_v1, bar@V1");
+__asm__(".symver bar_v2, bar@@V2");
+"#,
+        &[],
+        &[&format!("-Wl,--version-script,{}", script.display())],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    unsafe {
+        let all = lib.get_all::<extern "C" fn() -> i32>("foo");
+        assert_eq!(all.len(), 1);
+        assert_eq!((all[0].1)(), 2);
+
+        // The 8-byte default is checked, not the 4-byte hidden version.
+        let bar = lib.get_checked::<i64>("bar").unwrap();
+        assert_eq!(**bar, 2);
+        assert_eq!(*lib.get_static::<i64>("bar").unwrap(), 2);
+        *lib.get_static_mut::<i64>("bar").unwrap() = 3;
+        assert_eq!(*lib.get_static::<i64>("bar").unwrap(), 3);
+    }
+}
+
+#[test]
+fn undefined_symbols_lists_imports() {
+    if !has_command("cc") {
+        eprintln!("skipping undefined symbol test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("undefined-symbols");
+    build_c_lib(
+        &dir,
+        "import_dep",
+        "int imported_helper(void) { return 4; }\n",
+        &[],
+        &[],
+    );
+    let lib = build_c_lib(
+        &dir,
+        "importer",
+        r#"
+int imported_helper(void);
+__attribute__((weak)) int optional_helper(void);
+int importer(void) { return imported_helper() + (optional_helper ? 1 : 0); }
+"#,
+        &["import_dep"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(&lib, OpenFlags::RTLD_NOW).unwrap();
+    let undefined = lib.undefined_symbols();
+    assert!(undefined.iter().any(|name| name == "imported_helper"));
+    assert!(!undefined.iter().any(|name| name == "optional_helper"));
+    assert!(!undefined.iter().any(|name| name == "importer"));
+}
+
+#[test]
+fn bss_symbols_are_zeroed_and_writable() {
+    if !has_command("cc") {
+        eprintln!("skipping BSS test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("bss");
+    // The data segment ends mid-page, so the start of the array shares a page with file
+    // contents and the rest lies in anonymous pages past the end of the file.
+    let path = build_c_lib(
+        &dir,
+        "bss",
+        r#"
+int bss_initialized[3] = { 1, 2, 3 };
+int bss_array[1000];
+int bss_read(int i) { return bss_array[i]; }
+"#,
+        &[],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    let array = unsafe { lib.get::<()>("bss_array").unwrap() }.into_raw() as *mut [i32; 1000];
+    unsafe {
+        assert!((*array).iter().all(|&v| v == 0));
+        (*array)[999] = 42;
+        (*array)[0] = 7;
+    }
+    let bss_read = unsafe { lib.get::<extern "C" fn(i32) -> i32>("bss_read").unwrap() };
+    assert_eq!(bss_read(999), 42);
+    assert_eq!(bss_read(0), 7);
+    let initialized =
+        unsafe { lib.get::<()>("bss_initialized").unwrap() }.into_raw() as *const [i32; 3];
+    assert_eq!(unsafe { *initialized }, [1, 2, 3]);
+}
+
+#[test]
+fn get_cstr_finds_non_utf8_names() {
+    if !has_command("cc") {
+        eprintln!("skipping non-UTF-8 symbol test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("non-utf8-symbol");
+    // The assembler takes the name as raw bytes; 0xe9 alone is not valid UTF-8.
+    let path = build_c_lib(
+        &dir,
+        "highbyte",
+        r#"
+int high_byte_target(void) { return 11; }
+__asm__(".globl \"high\xe9" "byte\"\n\t.set \"high\xe9" "byte\", high_byte_target");
+"#,
+        &[],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL).unwrap();
+    let name = std::ffi::CStr::from_bytes_with_nul(b"high\xe9byte\0").unwrap();
+    let addr = lib.get_cstr(name).unwrap();
+    let target = unsafe {
+        lib.get::<extern "C" fn() -> i32>("high_byte_target")
+            .unwrap()
+    };
+    assert_eq!(addr, dlopen_rs::SymbolAddr::from(target));
+    let f: extern "C" fn() -> i32 = unsafe { std::mem::transmute(addr.as_ptr()) };
+    assert_eq!(f(), 11);
+    assert!(lib.get_cstr(c"high_byte_target").is_ok());
+
+    // The C entry point matches the raw bytes through a handle and in the global scope.
+    let handle = &lib as *const ElfLibrary as *const std::ffi::c_void;
+    let by_handle = unsafe { dlopen_rs::api::dlsym(handle, name.as_ptr()) };
+    let by_default = unsafe { dlopen_rs::api::dlsym(std::ptr::null(), name.as_ptr()) };
+    assert_eq!(by_handle.cast::<()>(), addr.as_ptr());
+    assert_eq!(by_default.cast::<()>(), addr.as_ptr());
+}
@@ -0,0 +1,546 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+#[test]
+fn get_local_ignores_dependency_exports() {
+    if !has_command("cc") {
+        eprintln!("skipping root-only lookup test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("get-local");
+    build_c_lib(
+        &dir,
+        "scope_dep",
+        r#"
+int shared_name(void) { return 2; }
+int dep_only(void) { return 3; }
+"#,
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "scope_root",
+        r#"
+int dep_only(void);
+int shared_name(void) { return 1; }
+int call_dep(void) { return dep_only(); }
+"#,
+        &["scope_dep"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    unsafe {
+        let local = lib
+            .get_local::<extern "C" fn() -> i32>("shared_name")
+            .unwrap();
+        assert_eq!(local(), 1);
+
+        // Only the searchlist lookup may reach into the dependency.
+        let dep_only = lib.get::<extern "C" fn() -> i32>("dep_only").unwrap();
+        assert_eq!(dep_only(), 3);
+        assert!(lib.get_local::<extern "C" fn() -> i32>("dep_only").is_err());
+    }
+}
+
+#[test]
+fn df_1_global_joins_global_scope() {
+    if !has_command("cc") {
+        eprintln!("skipping DF_1_GLOBAL test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("df-1-global");
+    let path = build_c_lib(
+        &dir,
+        "dfglobal",
+        "int df_global_value(void) { return 9; }\n",
+        &[],
+        &["-Wl,-z,global"],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    assert!(lib.flags().contains(OpenFlags::RTLD_GLOBAL));
+    let value = dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("df_global_value")
+        .expect("DF_1_GLOBAL library must be in the global scope");
+    assert_eq!(value(), 9);
+}
+
+#[test]
+fn local_dependency_stays_local() {
+    if !has_command("cc") {
+        eprintln!("skipping RTLD_LOCAL dependency test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("local-dependency");
+    let dep = build_c_lib(
+        &dir,
+        "localb",
+        "int local_b_value(void) { return 11; }\n",
+        &[],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "globala",
+        r#"
+int local_b_value(void);
+int global_a_value(void) { return local_b_value() + 1; }
+"#,
+        &["localb"],
+        &[],
+    );
+
+    let _dep = ElfLibrary::dlopen(dep.to_str().unwrap(), OpenFlags::RTLD_LOCAL).unwrap();
+    let root = ElfLibrary::dlopen(
+        root.to_str().unwrap(),
+        OpenFlags::RTLD_GLOBAL | OpenFlags::RTLD_LAZY,
+    )
+    .unwrap();
+
+    unsafe {
+        // The dependency is part of the root's searchlist and of its lazy binding scope.
+        let b = root.get::<extern "C" fn() -> i32>("local_b_value").unwrap();
+        assert_eq!(b(), 11);
+        let a = root
+            .get::<extern "C" fn() -> i32>("global_a_value")
+            .unwrap();
+        assert_eq!(a(), 12);
+    }
+    assert!(dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("global_a_value").is_ok());
+    assert!(dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("local_b_value").is_err());
+}
+
+#[test]
+fn get_all_reports_duplicate_definitions() {
+    if !has_command("cc") {
+        eprintln!("skipping duplicate symbol test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("get-all");
+    build_c_lib(&dir, "dupa", "int dup_sym(void) { return 1; }\n", &[], &[]);
+    build_c_lib(&dir, "dupb", "int dup_sym(void) { return 2; }\n", &[], &[]);
+    let root = build_c_lib(
+        &dir,
+        "duproot",
+        "int dup_root(void) { return 0; }\n",
+        &["dupa", "dupb"],
+        &["-Wl,--no-as-needed"],
+    );
+
+    let lib = ElfLibrary::dlopen(root.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let all = unsafe { lib.get_all::<extern "C" fn() -> i32>("dup_sym") };
+    assert_eq!(all.len(), 2);
+    assert!(all[0].0.ends_with("libdupa.so"));
+    assert!(all[1].0.ends_with("libdupb.so"));
+    assert_ne!(*all[0].1 as usize, *all[1].1 as usize);
+    assert_eq!((all[0].1)(), 1);
+    assert_eq!((all[1].1)(), 2);
+
+    let first = unsafe { lib.get::<extern "C" fn() -> i32>("dup_sym").unwrap() };
+    assert_eq!(*first as usize, *all[0].1 as usize);
+}
+
+#[test]
+fn promoted_library_leaves_global_scope() {
+    if !has_command("cc") {
+        eprintln!("skipping promotion unload test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("promote-unload");
+    let path = build_c_lib(
+        &dir,
+        "promoteunload",
+        "int promote_unload_value(void) { return 13; }\n",
+        &[],
+        &[],
+    );
+    let path = path.to_str().unwrap();
+
+    let local = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("promote_unload_value").is_err());
+    let global = ElfLibrary::dlopen(path, OpenFlags::RTLD_GLOBAL).unwrap();
+    let value = dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("promote_unload_value")
+        .expect("promoted library must be in the global scope");
+    assert_eq!(value(), 13);
+    drop(value);
+
+    let cloned = global.clone();
+    drop(global);
+    assert_eq!(local.close(), dlopen_rs::UnloadOutcome::StillReferenced);
+    assert!(matches!(
+        cloned.close(),
+        dlopen_rs::UnloadOutcome::Unloaded { .. }
+    ));
+    assert!(dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("promote_unload_value").is_err());
+    assert!(ElfLibrary::dlopen(path, OpenFlags::RTLD_NOLOAD).is_err());
+}
+
+#[test]
+fn resolver_substitutes_single_function() {
+    if !has_command("cc") {
+        eprintln!("skipping resolver test because cc is unavailable");
+        return;
+    }
+
+    extern "C" fn fixed_time(out: *mut i64) -> i64 {
+        if !out.is_null() {
+            unsafe { *out = 1234 };
+        }
+        1234
+    }
+
+    let dir = test_work_dir("resolver");
+    let source = r#"
+#include <time.h>
+#include <stdlib.h>
+long plugin_now(void) { return (long)time(NULL); }
+int plugin_abs(int v) { return abs(v); }
+"#;
+    for (name, flags) in [
+        ("resolver_now", OpenFlags::RTLD_NOW),
+        ("resolver_lazy", OpenFlags::RTLD_LAZY),
+    ] {
+        let path = build_c_lib(&dir, name, source, &[], &[]);
+        let lib = ElfLibrary::dlopen_with_resolver(path.to_str().unwrap(), flags, |name| {
+            (name == "time").then_some(fixed_time as *const ())
+        })
+        .unwrap();
+        let plugin_now = unsafe { lib.get::<extern "C" fn() -> i64>("plugin_now").unwrap() };
+        let plugin_abs = unsafe { lib.get::<extern "C" fn(i32) -> i32>("plugin_abs").unwrap() };
+        assert_eq!(plugin_now(), 1234, "{name}");
+        // Names the resolver declines still bind through the normal scopes.
+        assert_eq!(plugin_abs(-5), 5, "{name}");
+    }
+}
+
+#[test]
+fn global_scope_first_definition_wins() {
+    if !has_command("cc") {
+        eprintln!("skipping global order test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("global-order");
+    let build = |name: &str, value: i32| {
+        build_c_lib(
+            &dir,
+            name,
+            &format!("int global_order_sym(void) {{ return {value}; }}\n"),
+            &[],
+            &[],
+        )
+    };
+    let first = build("order_first", 1);
+    let promoted = build("order_promoted", 2);
+    let second = build("order_second", 3);
+    let global_order_sym = || unsafe {
+        dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("global_order_sym").unwrap()()
+    };
+
+    let first = ElfLibrary::dlopen(
+        first.to_str().unwrap(),
+        OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL,
+    )
+    .unwrap();
+    let promoted = promoted.to_str().unwrap();
+    let local = ElfLibrary::dlopen(promoted, OpenFlags::RTLD_NOW).unwrap();
+    let second = ElfLibrary::dlopen(
+        second.to_str().unwrap(),
+        OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL,
+    )
+    .unwrap();
+    assert_eq!(global_order_sym(), 1);
+
+    // Promotion appends to the global scope; it does not move ahead of `second`.
+    let promoted = ElfLibrary::dlopen(
+        promoted,
+        OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL | OpenFlags::RTLD_NOLOAD,
+    )
+    .unwrap();
+    assert_eq!(global_order_sym(), 1);
+
+    drop(first);
+    assert_eq!(global_order_sym(), 3);
+    drop(second);
+    assert_eq!(global_order_sym(), 2);
+    drop((local, promoted));
+}
+
+#[test]
+fn promote_global_in_place() {
+    if !has_command("cc") {
+        eprintln!("skipping in-place promotion test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("promote-in-place");
+    let path = build_c_lib(
+        &dir,
+        "promoteinplace",
+        "int promote_in_place_value(void) { return 21; }\n",
+        &[],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(!lib.is_global());
+    assert!(dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("promote_in_place_value").is_err());
+
+    lib.promote_global();
+    lib.promote_global();
+    assert!(lib.is_global());
+    assert!(lib.flags().contains(OpenFlags::RTLD_GLOBAL));
+    let value = dlopen_rs::dlsym_default::<extern "C" fn() -> i32>("promote_in_place_value")
+        .expect("promoted library must be in the global scope");
+    assert_eq!(value(), 21);
+}
+
+#[test]
+fn get_or_global_falls_back_to_global_scope() {
+    if !has_command("cc") {
+        eprintln!("skipping global fallback test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("get-or-global");
+    let provider = build_c_lib(
+        &dir,
+        "globalprovider",
+        "int global_only_value(void) { return 77; }\n",
+        &[],
+        &[],
+    );
+    let user = build_c_lib(
+        &dir,
+        "globaluser",
+        "int global_user_value(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+
+    let _provider = ElfLibrary::dlopen(provider.to_str().unwrap(), OpenFlags::RTLD_GLOBAL).unwrap();
+    let user = ElfLibrary::dlopen(user.to_str().unwrap(), OpenFlags::RTLD_LOCAL).unwrap();
+    unsafe {
+        assert!(
+            user.get::<extern "C" fn() -> i32>("global_only_value")
+                .is_err()
+        );
+        let value = user
+            .get_or_global::<extern "C" fn() -> i32>("global_only_value")
+            .unwrap();
+        assert_eq!(value(), 77);
+        let own = user
+            .get_or_global::<extern "C" fn() -> i32>("global_user_value")
+            .unwrap();
+        assert_eq!(own(), 1);
+        assert!(
+            user.get_or_global::<extern "C" fn() -> i32>("no_such_value")
+                .is_err()
+        );
+    }
+}
+
+#[test]
+fn get_skips_undefined_references() {
+    if !has_command("cc") {
+        eprintln!("skipping undefined reference test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("undefined-reference");
+    build_c_lib(
+        &dir,
+        "definer",
+        "int imported_sym(void) { return 64; }\n",
+        &[],
+        &[],
+    );
+    // The importer comes first in its own searchlist but only references the symbol.
+    let importer = build_c_lib(
+        &dir,
+        "importer",
+        "int imported_sym(void);\nint importer_call(void) { return imported_sym(); }\n",
+        &["definer"],
+        &[],
+    );
+
+    let lib = ElfLibrary::dlopen(importer.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let definer = ElfLibrary::dlopen(
+        dir.join("libdefiner.so").to_str().unwrap(),
+        OpenFlags::RTLD_NOLOAD,
+    )
+    .unwrap();
+    unsafe {
+        assert!(
+            lib.get_local::<extern "C" fn() -> i32>("imported_sym")
+                .is_err()
+        );
+        let sym = lib.get::<extern "C" fn() -> i32>("imported_sym").unwrap();
+        let expected = definer
+            .get_local::<extern "C" fn() -> i32>("imported_sym")
+            .unwrap();
+        assert_eq!(*sym as usize, *expected as usize);
+        assert_eq!(sym(), 64);
+    }
+    let info = lib.symbol_info("imported_sym").unwrap();
+    assert!(info.library.ends_with("libdefiner.so"), "{}", info.library);
+}
+
+#[test]
+fn filter_library_defers_to_filtee() {
+    if !has_command("cc") {
+        eprintln!("skipping filter library test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("filter-lib");
+    build_c_lib(
+        &dir,
+        "filtee",
+        "int resolve_me(void) { return 2; }\n",
+        &[],
+        &[],
+    );
+    let filter = build_c_lib(
+        &dir,
+        "filter",
+        "int resolve_me(void) { return 1; }\n",
+        &[],
+        &["-Wl,--filter=$ORIGIN/libfiltee.so"],
+    );
+    // A missing auxiliary filtee leaves the library answering for itself.
+    let auxiliary = build_c_lib(
+        &dir,
+        "auxfilter",
+        "int aux_only(void) { return 3; }\n",
+        &[],
+        &["-Wl,--auxiliary=$ORIGIN/libmissing.so"],
+    );
+
+    // A library loaded in the same `dlopen` as the filter binds to the filtee too.
+    let consumer = build_c_lib(
+        &dir,
+        "filteruser",
+        "int resolve_me(void);\nint call_resolve_me(void) { return resolve_me(); }\n",
+        &["filter"],
+        &[],
+    );
+
+    let user = ElfLibrary::dlopen(&consumer, OpenFlags::RTLD_NOW).unwrap();
+    let call_resolve_me = unsafe {
+        user.get::<extern "C" fn() -> i32>("call_resolve_me")
+            .unwrap()
+    };
+    assert_eq!(call_resolve_me(), 2);
+
+    let lib = ElfLibrary::dlopen(&filter, OpenFlags::RTLD_NOW).unwrap();
+    let resolve_me = unsafe { lib.get::<extern "C" fn() -> i32>("resolve_me").unwrap() };
+    assert_eq!(resolve_me(), 2);
+
+    let lib = ElfLibrary::dlopen(&auxiliary, OpenFlags::RTLD_NOW).unwrap();
+    let aux_only = unsafe { lib.get::<extern "C" fn() -> i32>("aux_only").unwrap() };
+    assert_eq!(aux_only(), 3);
+}
+
+#[test]
+fn diamond_resolution_matches_glibc() {
+    if !has_command("cc") {
+        eprintln!("skipping resolution order test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("diamond-order");
+    build_c_lib(
+        &dir,
+        "diamond_bottom",
+        "int diamond_which(void) { return 3; }\n",
+        &[],
+        &[],
+    );
+    build_c_lib(
+        &dir,
+        "diamond_left",
+        "int diamond_left(void) { return 1; }\n",
+        &["diamond_bottom"],
+        &[],
+    );
+    build_c_lib(
+        &dir,
+        "diamond_right",
+        "int diamond_which(void) { return 2; }\n",
+        &["diamond_bottom"],
+        &[],
+    );
+    let root = build_c_lib(
+        &dir,
+        "diamond_root",
+        "int diamond_which(void);\nint diamond_probe(void) { return diamond_which(); }\n",
+        &["diamond_left", "diamond_right"],
+        &[],
+    );
+
+    // Breadth first in DT_NEEDED order: root, left, right, bottom. The definition one level
+    // down in `right` wins over the one two levels down in `bottom`.
+    let (glibc_which, glibc_probe) = unsafe {
+        let lib = libloading::Library::new(&root).unwrap();
+        let which = lib.get::<extern "C" fn() -> i32>(b"diamond_which").unwrap();
+        let probe = lib.get::<extern "C" fn() -> i32>(b"diamond_probe").unwrap();
+        (which(), probe())
+    };
+    assert_eq!(glibc_which, 2);
+
+    let lib = ElfLibrary::dlopen(&root, OpenFlags::RTLD_NOW).unwrap();
+    let which = unsafe { lib.get::<extern "C" fn() -> i32>("diamond_which").unwrap() };
+    let probe = unsafe { lib.get::<extern "C" fn() -> i32>("diamond_probe").unwrap() };
+    assert_eq!(which(), glibc_which);
+    assert_eq!(probe(), glibc_probe);
+}
+
+#[test]
+fn protected_symbols_bind_to_own_definition() {
+    if !has_command("cc") {
+        eprintln!("skipping protected visibility test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("protected-visibility");
+    let interposer = build_c_lib(
+        &dir,
+        "protinterpose",
+        "int prot_shadowed_value = 2;\nint prot_shadowed_fn(void) { return 2; }\n",
+        &[],
+        &[],
+    );
+    // Protected data is still accessed through the GOT, so this takes a dynamic relocation.
+    let definer = build_c_lib(
+        &dir,
+        "protdefine",
+        r#"
+__attribute__((visibility("protected"))) int prot_shadowed_value = 1;
+__attribute__((visibility("protected"))) int prot_shadowed_fn(void) { return 1; }
+int read_prot_value(void) { return prot_shadowed_value; }
+int call_prot_fn(void) { return prot_shadowed_fn(); }
+"#,
+        &[],
+        &[],
+    );
+
+    let _global =
+        ElfLibrary::dlopen(&interposer, OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL).unwrap();
+    let lib = ElfLibrary::dlopen(&definer, OpenFlags::RTLD_NOW).unwrap();
+    let read_value = unsafe {
+        lib.get::<extern "C" fn() -> i32>("read_prot_value")
+            .unwrap()
+    };
+    let call_fn = unsafe { lib.get::<extern "C" fn() -> i32>("call_prot_fn").unwrap() };
+    assert_eq!(read_value(), 1);
+    assert_eq!(call_fn(), 1);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::{sync::Mutex, thread};
+
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl log::Log for Capture {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        LINES.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+#[test]
+fn concurrent_loads_are_tagged_with_their_thread() {
+    if !has_command("cc") {
+        eprintln!("skipping thread tag test because cc is unavailable");
+        return;
+    }
+    log::set_logger(&Capture).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let dir = test_work_dir("concurrent");
+
+    let loaders = ["tagged_a", "tagged_b"].map(|name| {
+        let source = format!("int {name}(void) {{ return 1; }}\n");
+        let path = build_c_lib(&dir, name, &source, &[], &[]);
+        thread::Builder::new()
+            .name(format!("loader-{name}"))
+            .spawn(move || {
+                let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+                assert!(matches!(
+                    lib.close(),
+                    dlopen_rs::UnloadOutcome::Unloaded { .. }
+                ));
+            })
+            .unwrap()
+    });
+    for loader in loaders {
+        loader.join().unwrap();
+    }
+
+    let lines = LINES.lock().unwrap();
+    for (name, other) in [("tagged_a", "tagged_b"), ("tagged_b", "tagged_a")] {
+        let tag = format!("[loader-{name} ThreadId(");
+        let own = lines
+            .iter()
+            .filter(|line| line.contains(&tag))
+            .collect::<Vec<_>>();
+        for step in ["Try to open", "Planning relocation", "Destroying dylib"] {
+            assert!(
+                own.iter()
+                    .any(|line| line.contains(step) && line.contains(name)),
+                "no [{step}] line for {name} in {own:?}"
+            );
+        }
+        assert!(own.iter().all(|line| !line.contains(other)), "{own:?}");
+    }
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{build_c_lib, has_command, test_work_dir};
+use dlopen_rs::{ElfLibrary, OpenFlags, api};
+use std::{
+    ffi::{CString, c_int, c_void},
+    path::PathBuf,
+    ptr,
+};
+
+#[test]
+fn many_tls_modules_from_new_thread() {
+    if !has_command("cc") {
+        eprintln!("skipping TLS module test because cc is unavailable");
+        return;
+    }
+
+    // More TLS-bearing modules than fit into one DTV slotinfo node.
+    let dir = test_work_dir("many-modules");
+    let libs = (0..25)
+        .map(|i| {
+            let source = format!(
+                "__thread int tls_value = {};\nint tls_get(void) {{ return tls_value; }}\n",
+                i + 100
+            );
+            let path = build_c_lib(&dir, &format!("tlsmod{i}"), &source, &[], &[]);
+            ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let lib = &libs[21];
+    let tls_get = unsafe { *lib.get::<extern "C" fn() -> i32>("tls_get").unwrap() };
+    let value = std::thread::spawn(move || tls_get()).join().unwrap();
+    assert_eq!(value, 121);
+    assert_eq!(tls_get(), 121);
+}
+
+#[test]
+fn tls_init_image_holds_initializer() {
+    if !has_command("cc") {
+        eprintln!("skipping TLS init image test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("init-image");
+    let path = build_c_lib(
+        &dir,
+        "tlsimage",
+        "__thread unsigned int tls_image_value = 0x12345678u;\nunsigned int tls_image_get(void) { return tls_image_value; }\n",
+        &[],
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path.to_str().unwrap(), OpenFlags::RTLD_NOW).unwrap();
+    let image = lib.tls_init_image().expect("library has a TLS segment");
+    assert_eq!(image[..4], 0x12345678u32.to_ne_bytes());
+}
+
+#[test]
+fn dlinfo_reports_tls_module() {
+    if !has_command("cc") {
+        eprintln!("skipping dlinfo TLS test because cc is unavailable");
+        return;
+    }
+
+    const RTLD_DI_TLS_MODID: c_int = 9;
+    const RTLD_DI_TLS_DATA: c_int = 10;
+
+    let dir = test_work_dir("dlinfo");
+    let tls_path = build_c_lib(
+        &dir,
+        "tlsinfo",
+        "__thread unsigned int tls_info_value = 0xcafef00du;\nunsigned int tls_info_get(void) { return tls_info_value; }\n",
+        &[],
+        &[],
+    );
+    let plain_path = build_c_lib(
+        &dir,
+        "notls",
+        "int notls_get(void) { return 1; }\n",
+        &[],
+        &[],
+    );
+
+    unsafe {
+        let path = CString::new(tls_path.to_str().unwrap()).unwrap();
+        let handle = api::dlopen(path.as_ptr(), OpenFlags::RTLD_NOW.bits() as _);
+        assert!(!handle.is_null());
+        let sym = CString::new("tls_info_get").unwrap();
+        let tls_info_get: extern "C" fn() -> u32 =
+            core::mem::transmute(api::dlsym(handle, sym.as_ptr()));
+        // Touch the variable so that this thread's block exists.
+        assert_eq!(tls_info_get(), 0xcafef00d);
+
+        let mut modid = 0usize;
+        let ret = api::dlinfo(handle, RTLD_DI_TLS_MODID, &mut modid as *mut usize as _);
+        assert_eq!(ret, 0);
+        assert!(modid > 0);
+
+        let mut data: *mut c_void = ptr::null_mut();
+        let ret = api::dlinfo(handle, RTLD_DI_TLS_DATA, &mut data as *mut *mut c_void as _);
+        assert_eq!(ret, 0);
+        assert!(!data.is_null());
+        assert_eq!(data.cast::<u32>().read_unaligned(), 0xcafef00d);
+
+        // A thread that never touched the variable gets its block allocated on request.
+        let handle_addr = handle as usize;
+        let fresh = std::thread::spawn(move || {
+            let mut data: *mut c_void = ptr::null_mut();
+            let ret = api::dlinfo(
+                handle_addr as *const c_void,
+                RTLD_DI_TLS_DATA,
+                &mut data as *mut *mut c_void as _,
+            );
+            assert_eq!(ret, 0);
+            assert!(!data.is_null());
+            data.cast::<u32>().read_unaligned()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(fresh, 0xcafef00d);
+        assert_eq!(api::dlclose(handle), 0);
+
+        let path = CString::new(plain_path.to_str().unwrap()).unwrap();
+        let handle = api::dlopen(path.as_ptr(), OpenFlags::RTLD_NOW.bits() as _);
+        assert!(!handle.is_null());
+        let mut modid = 0usize;
+        let ret = api::dlinfo(handle, RTLD_DI_TLS_MODID, &mut modid as *mut usize as _);
+        assert_eq!(ret, -1);
+        assert!(!api::dlerror().is_null());
+        assert_eq!(api::dlclose(handle), 0);
+    }
+}
+
+#[test]
+fn thread_local_symbols_resolve_per_thread() {
+    if !has_command("cc") {
+        eprintln!("skipping thread-local symbol test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("thread-local-symbol");
+    let path = build_c_lib(
+        &dir,
+        "tls_symbol",
+        r#"
+__thread int tls_value = 5;
+int *tls_value_addr(void) { return &tls_value; }
+"#,
+        &[],
+        &[],
+    );
+
+    fn resolve(lib: &ElfLibrary, set: i32) -> usize {
+        let value = unsafe { lib.get_thread_local::<i32>("tls_value").unwrap() };
+        let own = unsafe {
+            lib.get::<extern "C" fn() -> *mut i32>("tls_value_addr")
+                .unwrap()
+        };
+        assert_eq!(value, own());
+        assert_eq!(unsafe { *value }, 5);
+        unsafe { *value = set };
+        value as usize
+    }
+
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    let main_addr = resolve(&lib, 6);
+    let other_addr = std::thread::spawn(move || {
+        let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOLOAD).unwrap();
+        resolve(&lib, 7)
+    })
+    .join()
+    .unwrap();
+    assert_ne!(main_addr, other_addr);
+    assert_eq!(unsafe { *(main_addr as *const i32) }, 6);
+    assert!(unsafe { lib.get_thread_local::<i32>("tls_value_addr") }.is_err());
+}
+
+#[test]
+fn tls_of_later_library_is_reachable_from_running_thread() {
+    if !has_command("cc") {
+        eprintln!("skipping late TLS test because cc is unavailable");
+        return;
+    }
+
+    let dir = test_work_dir("late-tls");
+    let (send_path, recv_path) = std::sync::mpsc::channel::<PathBuf>();
+    // The thread exists, and has set up its TLS, before the library gets a module id.
+    let running = std::thread::spawn(move || {
+        let path = recv_path.recv().unwrap();
+        let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOLOAD).unwrap();
+        let read = unsafe { lib.get::<extern "C" fn() -> i32>("read_late_tls").unwrap() };
+        let value = unsafe { lib.get_thread_local::<i32>("late_tls").unwrap() };
+        (read(), unsafe { *value })
+    });
+
+    let path = build_c_lib(
+        &dir,
+        "late_tls",
+        r#"
+__thread int late_tls = 9;
+int read_late_tls(void) { return late_tls++; }
+"#,
+        &[],
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_NOW).unwrap();
+    send_path.send(path).unwrap();
+    assert_eq!(running.join().unwrap(), (9, 10));
+    drop(lib);
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "trace-resolution")]
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::env::consts;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+const TARGET_DIR: Option<&'static str> = option_env!("CARGO_TARGET_DIR");
+static TARGET_TRIPLE: OnceLock<String> = OnceLock::new();
+
+fn lib_path(file_name: &str) -> String {
+    let path: PathBuf = TARGET_DIR.unwrap_or("target").into();
+    path.join(TARGET_TRIPLE.get().unwrap())
+        .join("release")
+        .join(file_name)
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+const PACKAGE_NAME: [&str; 1] = ["example_dylib"];
+
+fn compile() {
+    static ONCE: ::std::sync::Once = ::std::sync::Once::new();
+    ONCE.call_once(|| {
+        let arch = consts::ARCH;
+        if arch.contains("x86_64") {
+            TARGET_TRIPLE
+                .set("x86_64-unknown-linux-gnu".to_string())
+                .unwrap();
+        } else if arch.contains("riscv64") {
+            TARGET_TRIPLE
+                .set("riscv64gc-unknown-linux-gnu".to_string())
+                .unwrap();
+        } else if arch.contains("aarch64") {
+            TARGET_TRIPLE
+                .set("aarch64-unknown-linux-gnu".to_string())
+                .unwrap();
+        }
+
+        for name in PACKAGE_NAME {
+            let mut cmd = ::std::process::Command::new("cargo");
+            cmd.arg("build")
+                .arg("-r")
+                .arg("-p")
+                .arg(name)
+                .env("CARGO_PROFILE_RELEASE_PANIC", "unwind")
+                .arg("--target")
+                .arg(TARGET_TRIPLE.get().unwrap().as_str());
+            assert!(
+                cmd.status()
+                    .expect("could not compile the test helpers!")
+                    .success()
+            );
+        }
+    });
+}
+
+/// Keeps every `resolution:` record so tests can inspect them.
+struct CaptureLogger {
+    records: Mutex<Vec<String>>,
+}
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        let msg = record.args().to_string();
+        if msg.starts_with("resolution:") {
+            self.records.lock().unwrap().push(msg);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+fn logger() -> &'static CaptureLogger {
+    static LOGGER: OnceLock<&'static CaptureLogger> = OnceLock::new();
+    LOGGER.get_or_init(|| {
+        let logger = Box::leak(Box::new(CaptureLogger {
+            records: Mutex::new(Vec::new()),
+        }));
+        log::set_logger(logger).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+        logger
+    })
+}
+
+#[test]
+fn deepbind_resolution_is_annotated() {
+    let logger = logger();
+    compile();
+    let path = lib_path("libexample.so");
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW | OpenFlags::RTLD_DEEPBIND).unwrap();
+    let add = unsafe { lib.get::<fn(i32, i32) -> i32>("add").unwrap() };
+    assert_eq!(add(1, 1), 2);
+
+    let records = logger.records.lock().unwrap();
+    assert!(
+        records
+            .iter()
+            .any(|r| r.contains("relocation scope deepbind: true")),
+        "missing deepbind annotation in {records:?}"
+    );
+    assert!(
+        records
+            .iter()
+            .any(|r| r.contains("symbol [add]") && r.contains("scope: local")),
+        "missing local resolution in {records:?}"
+    );
+}